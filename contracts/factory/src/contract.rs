@@ -10,13 +10,22 @@ use crate::{
 };
 
 use common::factory::Config;
-use common::market::InstantiateMsg as MarketInstantiateMsg;
+use common::market::{DealLimits, InstantiateMsg as MarketInstantiateMsg};
+use market::msg::ExecuteMsg as MarketExecuteMsg;
 
 const CONTRACT_NAME: &str = "crates.io/cw-otc-factory";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// ID of the reply call expected from the market creation.
 const INSTANTIATE_MARKET_REPLY_ID: u64 = 1;
+/// ID of the reply call expected from the failure of the first leg of a bundle. The following
+/// legs use the next IDs.
+const BUNDLE_LEG_REPLY_ID: u64 = 100;
+
+/// Number of denied addresses returned if not specified.
+pub const DEFAULT_DENYLIST_LIMIT: u32 = 10;
+/// Maximum number of denied addresses returned.
+pub const MAX_DENYLIST_LIMIT: u32 = 30;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -27,7 +36,8 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    let owner = deps.api.addr_validate(&msg.owner)?;
+    cw_ownable::initialize_owner(deps.storage, deps.api, Some(&msg.owner))?;
+
     let fee_collector = msg
         .fee_collector
         .as_ref()
@@ -37,9 +47,10 @@ pub fn instantiate(
     CONFIG.save(
         deps.storage,
         &Config {
-            owner,
             market_code_id: msg.market_code_id,
             fee_collector,
+            positions_code_id: msg.positions_code_id,
+            denied_can_withdraw: msg.denied_can_withdraw.unwrap_or_default(),
         },
     )?;
 
@@ -56,14 +67,50 @@ pub fn execute(
     use ExecuteMsg::*;
     match msg {
         UpdateConfig {
-            new_owner,
             new_fee_collector,
-        } => execute::update_config(deps, env, &info.sender, new_owner, new_fee_collector),
+            new_denied_can_withdraw,
+        } => execute::update_config(
+            deps,
+            env,
+            &info.sender,
+            new_fee_collector,
+            new_denied_can_withdraw,
+        ),
         CreateMarket {
             first_coin,
             second_coin,
             fee,
-        } => execute::create_market(deps, &info.sender, first_coin, second_coin, fee),
+            limits,
+            group,
+            oracle,
+            other_coins,
+            router,
+        } => execute::create_market(
+            deps,
+            env,
+            &info.sender,
+            first_coin,
+            second_coin,
+            fee,
+            limits,
+            group,
+            oracle,
+            other_coins,
+            router,
+        ),
+        UpdateMarketLimits {
+            first_coin,
+            second_coin,
+            limits,
+        } => execute::update_market_limits(deps, &info.sender, first_coin, second_coin, limits),
+        UpdateMarketOwnership {
+            first_coin,
+            second_coin,
+            action,
+        } => execute::update_market_ownership(deps, &info.sender, first_coin, second_coin, action),
+        UpdateDenylist { add, remove } => execute::update_denylist(deps, &info.sender, add, remove),
+        ExecuteBundle { legs } => execute::execute_bundle(deps, info, legs),
+        UpdateOwnership(action) => execute::update_ownership(deps, env, &info.sender, action),
     }
 }
 
@@ -77,25 +124,37 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             second_denom,
         } => to_json_binary(&query::get_market(deps, first_denom, second_denom)?),
         AllMarkets {} => to_json_binary(&query::get_all_markets(deps)?),
+        IsDenied { address } => to_json_binary(&query::get_is_denied(deps, address)?),
+        Denylist { start_after, limit } => {
+            to_json_binary(&query::get_denylist(deps, start_after, limit)?)
+        }
+        Ownership {} => to_json_binary(&cw_ownable::get_ownership(deps.storage)?),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
-    let msg_id = msg.id;
-    let res = cw_utils::parse_reply_instantiate_data(msg).map_err(|_| {
-        StdError::parse_err("MsgInstantiateContractResponse", "failed to parse data")
-    })?;
-    match msg_id {
-        INSTANTIATE_MARKET_REPLY_ID => reply::handle_instantiate_reply(deps, res),
+    match msg.id {
+        INSTANTIATE_MARKET_REPLY_ID => {
+            let res = cw_utils::parse_reply_instantiate_data(msg).map_err(|_| {
+                StdError::parse_err("MsgInstantiateContractResponse", "failed to parse data")
+            })?;
+            reply::handle_instantiate_reply(deps, res)
+        }
+        id if id >= BUNDLE_LEG_REPLY_ID => {
+            reply::handle_bundle_leg_reply(id - BUNDLE_LEG_REPLY_ID, msg.result)
+        }
         _ => Err(ContractError::UnknownReply {}),
     }
 }
 
 pub mod execute {
-    use cosmwasm_std::{Addr, Attribute, Decimal, ReplyOn, SubMsg, WasmMsg};
+    use cosmwasm_std::{Addr, Attribute, Decimal, Empty, ReplyOn, SubMsg, WasmMsg};
+    use cw_utils::NativeBalance;
 
-    use crate::state::TMP_MARKET_KEY;
+    use crate::msg::BundleLeg;
+
+    use crate::state::{DENYLIST, TMP_MARKET_KEYS};
 
     use super::*;
 
@@ -104,25 +163,31 @@ pub mod execute {
         deps: DepsMut,
         _env: Env,
         sender: &Addr,
-        new_owner: Option<String>,
         new_fee_collector: Option<String>,
+        new_denied_can_withdraw: Option<bool>,
     ) -> Result<Response, ContractError> {
         let mut config = CONFIG.load(deps.storage)?;
-        ensure!(config.owner == sender, ContractError::Unauthorized);
+        ensure!(
+            cw_ownable::is_owner(deps.storage, sender)?,
+            ContractError::Unauthorized
+        );
 
         let mut attributes = vec![];
 
-        if let Some(new_owner_addr) = new_owner {
-            config.owner = deps.api.addr_validate(&new_owner_addr)?;
-            attributes.push(Attribute::new("new_owner", config.owner.clone()));
-        }
-
         if let Some(new_fee_collector_addr) = new_fee_collector {
             let new_address = deps.api.addr_validate(&new_fee_collector_addr)?;
             config.fee_collector = Some(new_address.clone());
             attributes.push(Attribute::new("new_fee_collector", new_address));
         }
 
+        if let Some(denied_can_withdraw) = new_denied_can_withdraw {
+            config.denied_can_withdraw = denied_can_withdraw;
+            attributes.push(Attribute::new(
+                "new_denied_can_withdraw",
+                denied_can_withdraw.to_string(),
+            ));
+        }
+
         CONFIG.save(deps.storage, &config)?;
         Ok(Response::new()
             .add_attribute("action", "update_config")
@@ -130,34 +195,53 @@ pub mod execute {
     }
 
     /// Create a new instance of the market contract with the provided parameters.
+    #[allow(clippy::too_many_arguments)]
     pub fn create_market(
         deps: DepsMut,
+        env: Env,
         sender: &Addr,
         first_coin: String,
         second_coin: String,
         fee: Decimal,
+        limits: Option<DealLimits>,
+        group: Option<String>,
+        oracle: Option<String>,
+        other_coins: Option<Vec<String>>,
+        router: Option<String>,
     ) -> Result<Response, ContractError> {
         let config = CONFIG.load(deps.storage)?;
-        ensure!(config.owner == sender, ContractError::Unauthorized);
+        ensure!(
+            cw_ownable::is_owner(deps.storage, sender)?,
+            ContractError::Unauthorized
+        );
 
-        let ordered_coins = order_strings(first_coin.clone(), second_coin.clone());
+        // A market with other coins is a basket, registered for every pair of its coins.
+        let mut coins = vec![first_coin.clone(), second_coin.clone()];
+        coins.extend(other_coins.clone().unwrap_or_default());
+        let market_keys = coin_pairs(&coins);
 
-        if MARKETS
-            .may_load(deps.storage, ordered_coins.clone())?
-            .is_some()
-        {
-            return Err(ContractError::MarketAlreadyExists {});
+        for market_key in &market_keys {
+            if MARKETS.has(deps.storage, market_key.clone()) {
+                return Err(ContractError::MarketAlreadyExists {});
+            }
         }
 
         let sub_msg: Vec<SubMsg> = vec![SubMsg {
             id: INSTANTIATE_MARKET_REPLY_ID,
             msg: WasmMsg::Instantiate {
-                admin: Some(config.owner.to_string()),
+                admin: Some(sender.to_string()),
                 code_id: config.market_code_id,
                 msg: to_json_binary(&MarketInstantiateMsg {
                     first_coin: first_coin.clone(),
                     second_coin: second_coin.clone(),
                     fee,
+                    positions_code_id: config.positions_code_id,
+                    limits,
+                    group,
+                    factory: Some(env.contract.address.to_string()),
+                    oracle,
+                    other_coins,
+                    router,
                 })?,
                 funds: vec![],
                 label: "Market pair".to_string(),
@@ -169,7 +253,7 @@ pub mod execute {
 
         // This store is used to keep track of the coins that will be used in the created market
         // to easily handle the reply.
-        TMP_MARKET_KEY.save(deps.storage, &ordered_coins)?;
+        TMP_MARKET_KEYS.save(deps.storage, &market_keys)?;
 
         Ok(Response::new()
             .add_attribute("action", "ceate_market")
@@ -177,12 +261,173 @@ pub mod execute {
             .add_attribute("second_coin", second_coin)
             .add_submessages(sub_msg))
     }
+
+    /// Forward the new deal limits to the market of the two coins, whose owner is the factory.
+    pub fn update_market_limits(
+        deps: DepsMut,
+        sender: &Addr,
+        first_coin: String,
+        second_coin: String,
+        limits: DealLimits,
+    ) -> Result<Response, ContractError> {
+        ensure!(
+            cw_ownable::is_owner(deps.storage, sender)?,
+            ContractError::Unauthorized
+        );
+
+        let market = MARKETS
+            .may_load(
+                deps.storage,
+                order_strings(first_coin.clone(), second_coin.clone()),
+            )?
+            .ok_or(ContractError::MarketNotFound {})?;
+
+        let msg = WasmMsg::Execute {
+            contract_addr: market.clone(),
+            msg: to_json_binary(&MarketExecuteMsg::UpdateLimits { limits })?,
+            funds: vec![],
+        };
+
+        Ok(Response::new()
+            .add_attribute("action", "update_market_limits")
+            .add_attribute("market", market)
+            .add_message(msg))
+    }
+
+    /// Forward the ownership action to the market of the two coins, whose owner is the factory.
+    pub fn update_market_ownership(
+        deps: DepsMut,
+        sender: &Addr,
+        first_coin: String,
+        second_coin: String,
+        action: cw_ownable::Action,
+    ) -> Result<Response, ContractError> {
+        ensure!(
+            cw_ownable::is_owner(deps.storage, sender)?,
+            ContractError::Unauthorized
+        );
+
+        let market = MARKETS
+            .may_load(
+                deps.storage,
+                order_strings(first_coin.clone(), second_coin.clone()),
+            )?
+            .ok_or(ContractError::MarketNotFound {})?;
+
+        let msg = WasmMsg::Execute {
+            contract_addr: market.clone(),
+            msg: to_json_binary(&MarketExecuteMsg::UpdateOwnership(action))?,
+            funds: vec![],
+        };
+
+        Ok(Response::new()
+            .add_attribute("action", "update_market_ownership")
+            .add_attribute("market", market)
+            .add_message(msg))
+    }
+
+    /// Add and remove addresses from the denylist consulted by the markets.
+    pub fn update_denylist(
+        deps: DepsMut,
+        sender: &Addr,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        ensure!(
+            cw_ownable::is_owner(deps.storage, sender)?,
+            ContractError::Unauthorized
+        );
+
+        for addr in &add {
+            let addr = deps.api.addr_validate(addr)?;
+            DENYLIST.save(deps.storage, &addr, &Empty {})?;
+        }
+        for addr in &remove {
+            let addr = deps.api.addr_validate(addr)?;
+            DENYLIST.remove(deps.storage, &addr);
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "update_denylist")
+            .add_attribute("added", add.len().to_string())
+            .add_attribute("removed", remove.len().to_string()))
+    }
+
+    /// Accept the deals of the bundle legs on behalf of the sender. Each leg is a sub message
+    /// replying on error, so that the failure of a leg reverts the whole bundle.
+    pub fn execute_bundle(
+        deps: DepsMut,
+        info: MessageInfo,
+        legs: Vec<BundleLeg>,
+    ) -> Result<Response, ContractError> {
+        if legs.is_empty() {
+            return Err(ContractError::EmptyBundle {});
+        }
+
+        let mut expected = NativeBalance(legs.iter().map(|leg| leg.coin.clone()).collect());
+        expected.normalize();
+        let mut sent = NativeBalance(info.funds);
+        sent.normalize();
+        if expected != sent {
+            return Err(ContractError::WrongFunds {});
+        }
+
+        let mut sub_msgs = Vec::with_capacity(legs.len());
+        for (index, leg) in legs.into_iter().enumerate() {
+            let market = MARKETS
+                .may_load(deps.storage, order_strings(leg.first_coin, leg.second_coin))?
+                .ok_or(ContractError::MarketNotFound {})?;
+            sub_msgs.push(SubMsg {
+                id: BUNDLE_LEG_REPLY_ID + index as u64,
+                msg: WasmMsg::Execute {
+                    contract_addr: market,
+                    msg: to_json_binary(&MarketExecuteMsg::AcceptDealFor {
+                        taker: info.sender.to_string(),
+                        creator: leg.creator,
+                        deal_id: leg.deal_id,
+                        expected_coin_in: leg.expected_coin_in,
+                        min_receive: leg.min_receive,
+                        expected_version: leg.expected_version,
+                    })?,
+                    funds: vec![leg.coin],
+                }
+                .into(),
+                gas_limit: None,
+                reply_on: ReplyOn::Error,
+            });
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "execute_bundle")
+            .add_attribute("taker", info.sender)
+            .add_attribute("legs", sub_msgs.len().to_string())
+            .add_submessages(sub_msgs))
+    }
+
+    /// Allows to propose, accept, or renounce the ownership of the factory. A new owner must
+    /// accept the ownership before the proposal expires to become effective.
+    pub fn update_ownership(
+        deps: DepsMut,
+        env: Env,
+        sender: &Addr,
+        action: cw_ownable::Action,
+    ) -> Result<Response, ContractError> {
+        let ownership = cw_ownable::update_ownership(deps, &env.block, sender, action)?;
+        Ok(Response::new()
+            .add_attribute("action", "update_ownership")
+            .add_attributes(ownership.into_attributes()))
+    }
 }
 
 pub mod query {
-    use cosmwasm_std::Order;
+    use common::factory::IsDeniedResponse;
+    use cosmwasm_std::{Addr, Order};
+    use cw_storage_plus::Bound;
 
-    use crate::msg::{AllMarketsResponse, MarketResponse};
+    use crate::{
+        msg::{AllMarketsResponse, DenylistResponse, MarketResponse},
+        state::DENYLIST,
+    };
 
     use super::*;
 
@@ -217,25 +462,82 @@ pub mod query {
             markets: all_markets,
         })
     }
+
+    /// Retrieve if an address is denied, together with the withdraw policy of denied addresses.
+    pub fn get_is_denied(deps: Deps, address: String) -> StdResult<IsDeniedResponse> {
+        let config = CONFIG.load(deps.storage)?;
+        let address = deps.api.addr_validate(&address)?;
+        Ok(IsDeniedResponse {
+            denied: DENYLIST.has(deps.storage, &address),
+            can_withdraw: config.denied_can_withdraw,
+        })
+    }
+
+    /// Retrieve the denied addresses after start_after, at most limit of them.
+    pub fn get_denylist(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<DenylistResponse> {
+        let limit = limit
+            .unwrap_or(DEFAULT_DENYLIST_LIMIT)
+            .min(MAX_DENYLIST_LIMIT) as usize;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+        let addresses = DENYLIST
+            .keys(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect::<StdResult<Vec<Addr>>>()?;
+        Ok(DenylistResponse { addresses })
+    }
 }
 
 pub mod reply {
+    use cosmwasm_std::SubMsgResult;
     use cw_utils::MsgInstantiateContractResponse;
 
-    use crate::state::TMP_MARKET_KEY;
+    use crate::state::TMP_MARKET_KEYS;
 
     use super::*;
 
-    /// Handle the replies to the contract. A single reply coming from market instantiation is
-    /// expected.
+    /// Handle the reply coming from market instantiation.
     pub fn handle_instantiate_reply(
         deps: DepsMut,
         res: MsgInstantiateContractResponse,
     ) -> Result<Response, ContractError> {
-        let market_key = TMP_MARKET_KEY.load(deps.storage)?;
-        MARKETS.save(deps.storage, market_key, &res.contract_address)?;
+        for market_key in TMP_MARKET_KEYS.load(deps.storage)? {
+            MARKETS.save(deps.storage, market_key, &res.contract_address)?;
+        }
         Ok(Response::new())
     }
+
+    /// Handle the reply coming from the failure of a bundle leg, failing the whole bundle.
+    pub fn handle_bundle_leg_reply(
+        leg: u64,
+        result: SubMsgResult,
+    ) -> Result<Response, ContractError> {
+        match result {
+            SubMsgResult::Ok(_) => Ok(Response::new()),
+            SubMsgResult::Err(reason) => Err(ContractError::BundleLegFailed { leg, reason }),
+        }
+    }
+}
+
+/// Helper function returning the ordered pairs among the coin denoms.
+pub fn coin_pairs(coins: &[String]) -> Vec<(String, String)> {
+    let mut pairs = vec![];
+    for (index, first) in coins.iter().enumerate() {
+        for second in &coins[index + 1..] {
+            pairs.push(order_strings(first.clone(), second.clone()));
+        }
+    }
+    pairs
 }
 
 /// Helper function used to order two coin denoms.
@@ -255,6 +557,7 @@ mod tests {
         testing::{mock_dependencies, mock_env, mock_info},
         Addr, StdError,
     };
+    use cw_ownable::{Action, Expiration, OwnershipError};
 
     use crate::msg::InstantiateMsg;
     use execute;
@@ -277,15 +580,23 @@ mod tests {
                 owner: OWNER.to_string(),
                 market_code_id: 0,
                 fee_collector: Some(OWNER.to_string()),
+                positions_code_id: None,
+                denied_can_withdraw: None,
             },
         )
         .unwrap();
 
-        let owner = CONFIG.load(deps.as_ref().storage).unwrap().owner;
+        let owner = cw_ownable::get_ownership(deps.as_ref().storage)
+            .unwrap()
+            .owner;
         let fee_collector = CONFIG.load(deps.as_ref().storage).unwrap().fee_collector;
 
         let owner_addr = Addr::unchecked(OWNER);
-        assert_eq!(owner_addr, owner, "expect proper owner to be set");
+        assert_eq!(
+            Some(owner_addr.clone()),
+            owner,
+            "expect proper owner to be set"
+        );
         assert_eq!(
             Some(owner_addr),
             fee_collector,
@@ -307,15 +618,19 @@ mod tests {
                 owner: OWNER.to_string(),
                 market_code_id: 0,
                 fee_collector: None,
+                positions_code_id: None,
+                denied_can_withdraw: None,
             },
         )
         .unwrap();
 
-        let owner = CONFIG.load(deps.as_ref().storage).unwrap().owner;
+        let owner = cw_ownable::get_ownership(deps.as_ref().storage)
+            .unwrap()
+            .owner;
         let fee_collector = CONFIG.load(deps.as_ref().storage).unwrap().fee_collector;
 
         let owner_addr = Addr::unchecked(OWNER);
-        assert_eq!(owner_addr, owner, "expect proper owner to be set");
+        assert_eq!(Some(owner_addr), owner, "expect proper owner to be set");
         assert_eq!(None, fee_collector, "expect fee_collector to be None");
     }
 
@@ -327,55 +642,31 @@ mod tests {
         let initial_owner = Addr::unchecked(OWNER);
         let initial_fee_collector = None;
         let config = Config {
-            owner: initial_owner.clone(),
             market_code_id: 0,
             fee_collector: initial_fee_collector,
+            positions_code_id: None,
+            denied_can_withdraw: false,
         };
 
+        cw_ownable::initialize_owner(&mut deps.storage, &deps.api, Some(OWNER)).unwrap();
         CONFIG.save(&mut deps.storage, &config).unwrap();
 
         // Change fee_collector
-        execute::update_config(
-            deps.as_mut(),
-            env.clone(),
-            &Addr::unchecked(OWNER),
-            Some(OWNER.to_owned()),
-            Some(OWNER.to_owned()),
-        )
-        .unwrap();
-
-        let owner = CONFIG.load(deps.as_ref().storage).unwrap().owner;
-        let fee_collector = CONFIG.load(deps.as_ref().storage).unwrap().fee_collector;
-
-        assert_eq!(initial_owner, owner, "expect same owner");
-        assert_eq!(
-            Some(initial_owner.clone()),
-            fee_collector,
-            "expect fee_collector to be changed"
-        );
-
-        // Change owner
         execute::update_config(
             deps.as_mut(),
             env,
             &Addr::unchecked(OWNER),
-            Some("spiderman".to_owned()),
             Some(OWNER.to_owned()),
+            None,
         )
         .unwrap();
 
-        let owner = CONFIG.load(deps.as_ref().storage).unwrap().owner;
         let fee_collector = CONFIG.load(deps.as_ref().storage).unwrap().fee_collector;
 
-        assert_eq!(
-            &Addr::unchecked("spiderman"),
-            owner,
-            "expect owner to be changed"
-        );
         assert_eq!(
             Some(initial_owner),
             fee_collector,
-            "expect same fee_collector"
+            "expect fee_collector to be changed"
         );
     }
 
@@ -387,11 +678,13 @@ mod tests {
         let owner = Addr::unchecked(OWNER);
         let fee_collector = None;
         let config = Config {
-            owner: owner.clone(),
             market_code_id: 0,
             fee_collector,
+            positions_code_id: None,
+            denied_can_withdraw: false,
         };
 
+        cw_ownable::initialize_owner(&mut deps.storage, &deps.api, Some(OWNER)).unwrap();
         CONFIG.save(&mut deps.storage, &config).unwrap();
 
         // Only owner can change
@@ -399,8 +692,8 @@ mod tests {
             deps.as_mut(),
             env.clone(),
             &Addr::unchecked("spiderman"),
-            Some("spiderman".to_owned()),
             Some(OWNER.to_owned()),
+            None,
         )
         .unwrap_err();
 
@@ -420,7 +713,7 @@ mod tests {
             env.clone(),
             &owner,
             Some("Spiderman".to_owned()),
-            Some(OWNER.to_owned()),
+            None,
         )
         .unwrap_err();
 
@@ -429,30 +722,99 @@ mod tests {
             ContractError::Std(StdError::generic_err(
                 "Invalid input: address not normalized",
             )),
-            "expect to fail because not valid owner address"
+            "expect to fail because not valid fee_collector address"
         );
 
         let new_config = CONFIG.load(deps.as_ref().storage).unwrap();
         assert_eq!(config, new_config, "expected unchanged config");
+    }
 
-        let err = execute::update_config(
+    #[test]
+    fn update_ownership_works() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+
+        let owner = Addr::unchecked(OWNER);
+        let spiderman = Addr::unchecked("spiderman");
+
+        cw_ownable::initialize_owner(&mut deps.storage, &deps.api, Some(OWNER)).unwrap();
+
+        // Propose a new owner
+        execute::update_ownership(
             deps.as_mut(),
             env.clone(),
             &owner,
-            Some(OWNER.to_owned()),
-            Some("Spiderman".to_owned()),
+            Action::TransferOwnership {
+                new_owner: spiderman.to_string(),
+                expiry: Some(Expiration::AtHeight(env.block.height + 10)),
+            },
+        )
+        .unwrap();
+
+        let ownership = cw_ownable::get_ownership(deps.as_ref().storage).unwrap();
+        assert_eq!(
+            Some(owner.clone()),
+            ownership.owner,
+            "expect owner unchanged until the proposal is accepted"
+        );
+        assert_eq!(
+            Some(spiderman.clone()),
+            ownership.pending_owner,
+            "expect pending owner to be set"
+        );
+
+        // Only the pending owner can accept
+        let err = execute::update_ownership(
+            deps.as_mut(),
+            env.clone(),
+            &Addr::unchecked("stepit"),
+            Action::AcceptOwnership,
         )
         .unwrap_err();
 
         assert_eq!(
             err,
-            ContractError::Std(StdError::generic_err(
-                "Invalid input: address not normalized",
-            )),
-            "expect to fail because not valid fee_collector address"
+            ContractError::Ownership(OwnershipError::NotPendingOwner),
+            "expect to fail because not the pending owner"
         );
 
-        let new_config = CONFIG.load(deps.as_ref().storage).unwrap();
-        assert_eq!(config, new_config, "expected unchanged config");
+        execute::update_ownership(
+            deps.as_mut(),
+            env.clone(),
+            &spiderman,
+            Action::AcceptOwnership,
+        )
+        .unwrap();
+
+        let ownership = cw_ownable::get_ownership(deps.as_ref().storage).unwrap();
+        assert_eq!(
+            Some(spiderman.clone()),
+            ownership.owner,
+            "expect owner to be changed"
+        );
+
+        // Expired proposals cannot be accepted
+        execute::update_ownership(
+            deps.as_mut(),
+            env.clone(),
+            &spiderman,
+            Action::TransferOwnership {
+                new_owner: owner.to_string(),
+                expiry: Some(Expiration::AtHeight(env.block.height + 10)),
+            },
+        )
+        .unwrap();
+
+        env.block.height += 11;
+        let err = execute::update_ownership(deps.as_mut(), env, &owner, Action::AcceptOwnership)
+            .unwrap_err();
+
+        assert!(
+            matches!(
+                err,
+                ContractError::Ownership(OwnershipError::TransferExpired)
+            ),
+            "expect to fail because proposal expired"
+        );
     }
 }
@@ -6,6 +6,9 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Ownership(#[from] cw_ownable::OwnershipError),
+
     #[error("Unauthorized")]
     Unauthorized,
 
@@ -14,4 +17,16 @@ pub enum ContractError {
 
     #[error("The market for the given coins already exists")]
     MarketAlreadyExists {},
+
+    #[error("The market for the given coins does not exist")]
+    MarketNotFound {},
+
+    #[error("Bundle must contain at least one leg")]
+    EmptyBundle {},
+
+    #[error("Sent funds are not equal to the coins of the bundle legs")]
+    WrongFunds {},
+
+    #[error("Leg {leg} of the bundle failed: {reason}")]
+    BundleLegFailed { leg: u64, reason: String },
 }
@@ -1,6 +1,10 @@
-use common::factory::Config;
+use common::{
+    factory::{Config, IsDeniedResponse},
+    market::DealLimits,
+};
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Decimal;
+use cosmwasm_std::{Addr, Coin, Decimal, Uint128};
+use cw_ownable::{cw_ownable_execute, cw_ownable_query};
 
 /// This struct contains required variables to instantiate a new factory.
 #[cw_serde]
@@ -11,17 +15,24 @@ pub struct InstantiateMsg {
     pub market_code_id: u64,
     /// The address that will receive otc markets fees.
     pub fee_collector: Option<String>,
+    /// Code ID of the positions contract. If specified, markets tokenize deal positions.
+    pub positions_code_id: Option<u64>,
+    /// If true, denied addresses can still withdraw the coins of their unmatched deals. Default
+    /// to false.
+    pub denied_can_withdraw: Option<bool>,
 }
 
-/// This enum describes available contract's execution messages.
+/// This enum describes available contract's execution messages. The ownership of the contract is
+/// updated with a two-step transfer through `UpdateOwnership`.
+#[cw_ownable_execute]
 #[cw_serde]
 pub enum ExecuteMsg {
     /// Allows to update the contract's configuration. Only owner can update.
     UpdateConfig {
-        /// New contract owner.
-        new_owner: Option<String>,
         /// New fee collector address.
         new_fee_collector: Option<String>,
+        /// New policy for the withdraw of unmatched deals by denied addresses.
+        new_denied_can_withdraw: Option<bool>,
     },
     /// Allows to instantiate a new market contract. The order of the coin is not relevant.
     CreateMarket {
@@ -31,10 +42,75 @@ pub enum ExecuteMsg {
         second_coin: String,
         /// Fee deducted from each clsoed deal.
         fee: Decimal,
+        /// Limits applied to the deals of the market.
+        limits: Option<DealLimits>,
+        /// Address of a CW4 group whose members are the only ones allowed to create or accept deals.
+        group: Option<String>,
+        /// Address of the price oracle consulted by deals with a price trigger.
+        oracle: Option<String>,
+        /// Other coins accepted by the deals of the market besides the first and second coin. The
+        /// market is a basket where any pair of its coins can be traded, and it is registered for
+        /// every pair.
+        other_coins: Option<Vec<String>>,
+        /// Address of the Astroport router used to swap the coin sent by takers paying with a
+        /// different token.
+        router: Option<String>,
     },
+    /// Allows to update the deal limits of an existing market. Only owner can update.
+    UpdateMarketLimits {
+        /// First coin exchanged in the market.
+        first_coin: String,
+        /// Second coin exchanged in the market.
+        second_coin: String,
+        /// New limits applied to the deals of the market.
+        limits: DealLimits,
+    },
+    /// Allows to update the ownership of an existing market, for example to transfer it to
+    /// another address or to renounce it. Only owner can update.
+    UpdateMarketOwnership {
+        /// First coin exchanged in the market.
+        first_coin: String,
+        /// Second coin exchanged in the market.
+        second_coin: String,
+        /// Ownership action executed by the factory on the market.
+        action: cw_ownable::Action,
+    },
+    /// Allows to add and remove addresses from the denylist consulted by the markets. Only owner
+    /// can update.
+    UpdateDenylist {
+        /// Addresses to add to the denylist.
+        add: Vec<String>,
+        /// Addresses to remove from the denylist.
+        remove: Vec<String>,
+    },
+    /// Allows to accept deals of different markets atomically: if any of the deals cannot be
+    /// accepted, none is. Sent funds must be equal to the sum of the coins of the legs.
+    ExecuteBundle { legs: Vec<BundleLeg> },
+}
+
+/// Deal of a market created by the factory to accept as part of a bundle.
+#[cw_serde]
+pub struct BundleLeg {
+    /// First coin exchanged in the market of the deal.
+    pub first_coin: String,
+    /// Second coin exchanged in the market of the deal.
+    pub second_coin: String,
+    /// Address of the deal creator.
+    pub creator: String,
+    /// Identifier of the deal.
+    pub deal_id: u64,
+    /// Coin sent to accept the deal.
+    pub coin: Coin,
+    /// If specified, the coin offered by the deal must be equal to this one.
+    pub expected_coin_in: Option<Coin>,
+    /// If specified, the amount received after fee must be at least this one.
+    pub min_receive: Option<Uint128>,
+    /// If specified, the deal must not have been amended after this version.
+    pub expected_version: Option<u64>,
 }
 
 /// This enum describes available contract's query messages.
+#[cw_ownable_query]
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
@@ -50,6 +126,15 @@ pub enum QueryMsg {
     #[returns(AllMarketsResponse)]
     /// Retrieve all markets.
     AllMarkets {},
+    /// Retrieve if an address is denied. Used by the markets.
+    #[returns(IsDeniedResponse)]
+    IsDenied { address: String },
+    /// Retrieve the denied addresses in ascending order.
+    #[returns(DenylistResponse)]
+    Denylist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
@@ -63,3 +148,9 @@ pub struct AllMarketsResponse {
     /// List all available markets.
     pub markets: Vec<((String, String), String)>,
 }
+
+#[cw_serde]
+pub struct DenylistResponse {
+    /// Denied addresses.
+    pub addresses: Vec<Addr>,
+}
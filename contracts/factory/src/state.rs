@@ -1,5 +1,6 @@
 use common::factory::Config;
 use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Empty};
 use cw_storage_plus::{Item, Map};
 
 #[cw_serde]
@@ -12,8 +13,12 @@ pub struct MarketPair {
 /// Single object storing contract's configuration.
 pub const CONFIG: Item<Config> = Item::new("config");
 
-/// Store used to temporarily store the key of a market that is being created.
-pub const TMP_MARKET_KEY: Item<(String, String)> = Item::new("tmp_market_key");
+/// Store used to temporarily store the keys of a market that is being created, one for each pair
+/// of its coins.
+pub const TMP_MARKET_KEYS: Item<Vec<(String, String)>> = Item::new("tmp_market_keys");
 
 // Store all available markets created through the factory.
 pub const MARKETS: Map<(String, String), String> = Map::new("markets");
+
+/// Addresses denied by the factory owner, consulted by the markets.
+pub const DENYLIST: Map<&Addr, Empty> = Map::new("denylist");
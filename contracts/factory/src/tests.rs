@@ -1 +1,5 @@
+mod bundle;
 mod create_market;
+mod denylist;
+mod update_market_limits;
+mod update_market_ownership;
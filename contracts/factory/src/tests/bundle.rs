@@ -0,0 +1,287 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Empty};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
+
+use crate::{
+    error::ContractError,
+    msg::{BundleLeg, ExecuteMsg, InstantiateMsg, MarketResponse, QueryMsg},
+};
+
+use common::market::DealStatus;
+use market::msg::{
+    DealsByCreatorResponse, ExecuteMsg as MarketExecuteMsg, QueryMsg as MarketQueryMsg,
+};
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        market::contract::execute,
+        market::contract::instantiate,
+        market::contract::query,
+    )
+    .with_reply(market::contract::reply);
+    Box::new(contract)
+}
+
+// Creates a factory contract
+pub fn factory_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    )
+    .with_reply_empty(crate::contract::reply);
+
+    Box::new(contract)
+}
+
+// Instantiates a factory, creates the astro-usdc and ntrn-usdc markets through it, and creates a
+// deal in each market: 0xtrustme sells 1_000 astro for 1_000 usdc and 0xdesk sells 500 ntrn for
+// 800 usdc.
+fn setup_bundle(app: &mut App, owner: &Addr) -> (Addr, Addr, Addr) {
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+    let desk = Addr::unchecked("0xdesk".to_string());
+
+    let market_id = app.store_code(market_contract());
+
+    let factory_id = app.store_code(factory_contract());
+    let init_factory_msg = InstantiateMsg {
+        owner: owner.to_string(),
+        market_code_id: market_id,
+        fee_collector: Some(owner.to_string()),
+        positions_code_id: None,
+        denied_can_withdraw: None,
+    };
+    let factory_addr = app
+        .instantiate_contract(
+            factory_id,
+            owner.clone(),
+            &init_factory_msg,
+            &[],
+            "factory-otc",
+            None,
+        )
+        .unwrap();
+
+    let mut markets = vec![];
+    for (first_coin, seller, coin_in, coin_out) in [
+        ("astro", &not_a_scammer, 1_000, 1_000),
+        ("ntrn", &desk, 500, 800),
+    ] {
+        app.execute_contract(
+            owner.clone(),
+            factory_addr.clone(),
+            &ExecuteMsg::CreateMarket {
+                first_coin: first_coin.to_string(),
+                second_coin: "usdc".to_string(),
+                fee: Decimal::percent(3),
+                limits: None,
+                group: None,
+                oracle: None,
+                other_coins: None,
+                router: None,
+            },
+            &[],
+        )
+        .unwrap();
+        let resp: MarketResponse = app
+            .wrap()
+            .query_wasm_smart(
+                factory_addr.clone(),
+                &QueryMsg::Market {
+                    first_denom: first_coin.to_string(),
+                    second_denom: "usdc".to_string(),
+                },
+            )
+            .unwrap();
+        let market_addr = Addr::unchecked(resp.address);
+
+        app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: seller.to_string(),
+            amount: vec![Coin::new(coin_in, first_coin)],
+        }))
+        .unwrap();
+        app.execute_contract(
+            seller.clone(),
+            market_addr.clone(),
+            &MarketExecuteMsg::CreateDeal {
+                coin_out: Coin::new(coin_out, "usdc"),
+                counterparties: vec![],
+                group: None,
+                expiry: Expiration::Never {},
+                match_crossing: None,
+                kind: None,
+                trigger: None,
+                coin_out_alternatives: vec![],
+            },
+            &[Coin::new(coin_in, first_coin)],
+        )
+        .unwrap();
+        markets.push(market_addr);
+    }
+
+    (factory_addr, markets[0].clone(), markets[1].clone())
+}
+
+// Returns the legs accepting the deals of the two markets with the given coin for the second one.
+fn bundle_legs(second_coin: Coin) -> Vec<BundleLeg> {
+    vec![
+        BundleLeg {
+            first_coin: "astro".to_string(),
+            second_coin: "usdc".to_string(),
+            creator: "0xtrustme".to_string(),
+            deal_id: 0,
+            coin: Coin::new(1_000, "usdc"),
+            expected_coin_in: None,
+            min_receive: None,
+            expected_version: None,
+        },
+        BundleLeg {
+            first_coin: "ntrn".to_string(),
+            second_coin: "usdc".to_string(),
+            creator: "0xdesk".to_string(),
+            deal_id: 0,
+            coin: second_coin,
+            expected_coin_in: None,
+            min_receive: None,
+            expected_version: None,
+        },
+    ]
+}
+
+#[test]
+fn execute_bundle_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked("owner".to_string());
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let (factory_addr, astro_market, ntrn_market) = setup_bundle(&mut app, &owner);
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_800, "usdc")],
+    }))
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            factory_addr.clone(),
+            &ExecuteMsg::ExecuteBundle {
+                legs: bundle_legs(Coin::new(800, "usdc")),
+            },
+            &[Coin::new(1_700, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::WrongFunds {},
+        "expected error because sent funds do not cover the legs"
+    );
+
+    // Markets accept deals on behalf of a taker only from their factory.
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            astro_market.clone(),
+            &MarketExecuteMsg::AcceptDealFor {
+                taker: stepit.to_string(),
+                creator: not_a_scammer.to_string(),
+                deal_id: 0,
+                expected_coin_in: None,
+                min_receive: None,
+                expected_version: None,
+            },
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    app.execute_contract(
+        stepit.clone(),
+        factory_addr,
+        &ExecuteMsg::ExecuteBundle {
+            legs: bundle_legs(Coin::new(800, "usdc")),
+        },
+        &[Coin::new(1_800, "usdc")],
+    )
+    .unwrap();
+
+    for (market_addr, creator) in [(astro_market, "0xtrustme"), (ntrn_market, "0xdesk")] {
+        let resp: DealsByCreatorResponse = app
+            .wrap()
+            .query_wasm_smart(
+                market_addr,
+                &MarketQueryMsg::DealsByCreator {
+                    creator: creator.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            resp.deals[0].1.counterparty,
+            Some(stepit.clone()),
+            "expected the sender of the bundle to be the counterparty"
+        );
+    }
+}
+
+#[test]
+fn execute_bundle_is_atomic() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked("owner".to_string());
+    let stepit = Addr::unchecked("0xstepit".to_string());
+
+    let (factory_addr, astro_market, _) = setup_bundle(&mut app, &owner);
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_700, "usdc")],
+    }))
+    .unwrap();
+
+    // The second leg sends less than requested by the deal.
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            factory_addr,
+            &ExecuteMsg::ExecuteBundle {
+                legs: bundle_legs(Coin::new(700, "usdc")),
+            },
+            &[Coin::new(1_700, "usdc")],
+        )
+        .unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::BundleLegFailed { leg: 1, .. })
+        ),
+        "expected error because the second leg failed"
+    );
+
+    let resp: DealsByCreatorResponse = app
+        .wrap()
+        .query_wasm_smart(
+            astro_market,
+            &MarketQueryMsg::DealsByCreator {
+                creator: "0xtrustme".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp.deals[0].1.status,
+        DealStatus::NotMatched,
+        "expected the first leg to be reverted"
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(&stepit, "usdc")
+            .unwrap()
+            .amount
+            .u128(),
+        1_700,
+        "expected the funds of the bundle to be refunded"
+    );
+}
@@ -1,12 +1,13 @@
 use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
 use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
 
 use crate::{
     error::ContractError,
     msg::{AllMarketsResponse, ExecuteMsg, InstantiateMsg, MarketResponse, QueryMsg},
 };
 
-use market::msg::QueryMsg as MarketQueryMsg;
+use market::msg::{ExecuteMsg as MarketExecuteMsg, OrderBookResponse, QueryMsg as MarketQueryMsg};
 
 // Creates a market contract.
 pub fn market_contract() -> Box<dyn Contract<Empty>> {
@@ -14,6 +15,17 @@ pub fn market_contract() -> Box<dyn Contract<Empty>> {
         market::contract::execute,
         market::contract::instantiate,
         market::contract::query,
+    )
+    .with_reply(market::contract::reply);
+    Box::new(contract)
+}
+
+// Creates a positions contract.
+pub fn positions_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        positions::contract::execute,
+        positions::contract::instantiate,
+        positions::contract::query,
     );
     Box::new(contract)
 }
@@ -47,6 +59,8 @@ fn create_market_works() {
         owner: owner.to_string(),
         market_code_id: market_id,
         fee_collector: Some(owner.to_string()),
+        positions_code_id: None,
+        denied_can_withdraw: None,
     };
     let factory_addr = app
         .instantiate_contract(
@@ -79,6 +93,11 @@ fn create_market_works() {
         first_coin: "astro".to_string(),
         second_coin: "usdc".to_string(),
         fee: Decimal::percent(3),
+        limits: None,
+        group: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
     };
     app.execute_contract(owner.clone(), factory_addr.clone(), &create_market_msg, &[])
         .unwrap();
@@ -120,6 +139,71 @@ fn create_market_works() {
     );
 }
 
+#[test]
+fn create_market_with_positions_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked("owner".to_string());
+
+    // Store the market and positions contracts.
+    let market_id = app.store_code(market_contract());
+    let positions_id = app.store_code(positions_contract());
+
+    // Store and instantiate the factory contract.
+    let factory_id = app.store_code(factory_contract());
+    let init_factory_msg = InstantiateMsg {
+        owner: owner.to_string(),
+        market_code_id: market_id,
+        fee_collector: Some(owner.to_string()),
+        positions_code_id: Some(positions_id),
+        denied_can_withdraw: None,
+    };
+    let factory_addr = app
+        .instantiate_contract(
+            factory_id,
+            owner.clone(),
+            &init_factory_msg,
+            &[],
+            "factory-otc",
+            None,
+        )
+        .unwrap();
+
+    let create_market_msg = ExecuteMsg::CreateMarket {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(3),
+        limits: None,
+        group: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    app.execute_contract(owner.clone(), factory_addr.clone(), &create_market_msg, &[])
+        .unwrap();
+
+    let resp_market: MarketResponse = app
+        .wrap()
+        .query_wasm_smart(
+            factory_addr.clone(),
+            &QueryMsg::Market {
+                first_denom: "astro".to_string(),
+                second_denom: "usdc".to_string(),
+            },
+        )
+        .unwrap();
+
+    let market_config: common::market::Config = app
+        .wrap()
+        .query_wasm_smart(resp_market.address, &MarketQueryMsg::Config {})
+        .unwrap();
+
+    assert!(
+        market_config.positions.is_some(),
+        "expected market to create the positions contract"
+    );
+}
+
 #[test]
 fn create_market_handle_errors() {
     let mut app: App = App::default();
@@ -137,6 +221,8 @@ fn create_market_handle_errors() {
         owner: owner.to_string(),
         market_code_id: market_id,
         fee_collector: Some(owner.to_string()),
+        positions_code_id: None,
+        denied_can_withdraw: None,
     };
     let factory_addr = app
         .instantiate_contract(
@@ -169,6 +255,11 @@ fn create_market_handle_errors() {
         first_coin: "astro".to_string(),
         second_coin: "usdc".to_string(),
         fee: Decimal::percent(3),
+        limits: None,
+        group: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
     };
     let err = app
         .execute_contract(
@@ -198,3 +289,155 @@ fn create_market_handle_errors() {
         "expected error because market already exists"
     );
 }
+
+#[test]
+fn create_basket_market_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked("owner".to_string());
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_id = app.store_code(market_contract());
+
+    let factory_id = app.store_code(factory_contract());
+    let init_factory_msg = InstantiateMsg {
+        owner: owner.to_string(),
+        market_code_id: market_id,
+        fee_collector: Some(owner.to_string()),
+        positions_code_id: None,
+        denied_can_withdraw: None,
+    };
+    let factory_addr = app
+        .instantiate_contract(
+            factory_id,
+            owner.clone(),
+            &init_factory_msg,
+            &[],
+            "factory-otc",
+            None,
+        )
+        .unwrap();
+
+    app.execute_contract(
+        owner.clone(),
+        factory_addr.clone(),
+        &ExecuteMsg::CreateMarket {
+            first_coin: "astro".to_string(),
+            second_coin: "usdc".to_string(),
+            fee: Decimal::percent(3),
+            limits: None,
+            group: None,
+            oracle: None,
+            other_coins: Some(vec!["ntrn".to_string(), "usdt".to_string()]),
+            router: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let resp_markets: AllMarketsResponse = app
+        .wrap()
+        .query_wasm_smart(factory_addr.clone(), &QueryMsg::AllMarkets {})
+        .unwrap();
+    assert_eq!(
+        resp_markets.markets.len(),
+        6,
+        "expected the basket registered for every pair of its coins"
+    );
+
+    let resp_market: MarketResponse = app
+        .wrap()
+        .query_wasm_smart(
+            factory_addr.clone(),
+            &QueryMsg::Market {
+                first_denom: "usdt".to_string(),
+                second_denom: "ntrn".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp_market.address, resp_markets.markets[0].1);
+
+    let err = app
+        .execute_contract(
+            owner,
+            factory_addr,
+            &ExecuteMsg::CreateMarket {
+                first_coin: "ntrn".to_string(),
+                second_coin: "usdt".to_string(),
+                fee: Decimal::percent(3),
+                limits: None,
+                group: None,
+                oracle: None,
+                other_coins: None,
+                router: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::MarketAlreadyExists {},
+        "expected error because the pair is part of the basket"
+    );
+
+    // Any pair of the basket can be traded, also without the first coin of the market.
+    let market_addr = Addr::unchecked(resp_market.address);
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "ntrn")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(500, "usdt")],
+    }))
+    .unwrap();
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &MarketExecuteMsg::CreateDeal {
+            coin_out: Coin::new(500, "usdt"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::Never {},
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(1_000, "ntrn")],
+    )
+    .unwrap();
+
+    let resp_book: OrderBookResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr.clone(),
+            &MarketQueryMsg::OrderBook {
+                depth: None,
+                first_denom: Some("ntrn".to_string()),
+                second_denom: Some("usdt".to_string()),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp_book.sell_first_coin[0].offered,
+        Uint128::new(1_000),
+        "expected the deal in the book of the pair"
+    );
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr,
+        &MarketExecuteMsg::MarketTake {
+            max_price: Decimal::one(),
+            min_receive: Uint128::new(970),
+            denom_out: Some("ntrn".to_string()),
+        },
+        &[Coin::new(500, "usdt")],
+    )
+    .unwrap();
+    let ntrn_balance = app.wrap().query_balance(&stepit, "ntrn").unwrap();
+    assert_eq!(ntrn_balance.amount, Uint128::new(970));
+}
@@ -0,0 +1,867 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Empty};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::{Expiration, Scheduled};
+
+use crate::{
+    error::ContractError,
+    msg::{DenylistResponse, ExecuteMsg, InstantiateMsg, MarketResponse, QueryMsg},
+};
+
+use common::{
+    factory::IsDeniedResponse,
+    market::{DealKind, Side},
+};
+use market::msg::{BalanceResponse, ExecuteMsg as MarketExecuteMsg, QueryMsg as MarketQueryMsg};
+use positions::msg::ExecuteMsg as PositionsExecuteMsg;
+
+use super::create_market::positions_contract;
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        market::contract::execute,
+        market::contract::instantiate,
+        market::contract::query,
+    )
+    .with_reply(market::contract::reply);
+    Box::new(contract)
+}
+
+// Creates a factory contract
+pub fn factory_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    )
+    .with_reply_empty(crate::contract::reply);
+
+    Box::new(contract)
+}
+
+// Instantiates a factory and creates the astro-usdc market through it.
+fn setup_factory(app: &mut App, owner: &Addr) -> (Addr, Addr) {
+    let market_id = app.store_code(market_contract());
+
+    let factory_id = app.store_code(factory_contract());
+    let init_factory_msg = InstantiateMsg {
+        owner: owner.to_string(),
+        market_code_id: market_id,
+        fee_collector: Some(owner.to_string()),
+        positions_code_id: None,
+        denied_can_withdraw: None,
+    };
+    let factory_addr = app
+        .instantiate_contract(
+            factory_id,
+            owner.clone(),
+            &init_factory_msg,
+            &[],
+            "factory-otc",
+            None,
+        )
+        .unwrap();
+
+    let create_market_msg = ExecuteMsg::CreateMarket {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(3),
+        limits: None,
+        group: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    app.execute_contract(owner.clone(), factory_addr.clone(), &create_market_msg, &[])
+        .unwrap();
+
+    let resp: MarketResponse = app
+        .wrap()
+        .query_wasm_smart(
+            factory_addr.clone(),
+            &QueryMsg::Market {
+                first_denom: "astro".to_string(),
+                second_denom: "usdc".to_string(),
+            },
+        )
+        .unwrap();
+
+    (factory_addr, Addr::unchecked(resp.address))
+}
+
+#[test]
+fn update_denylist_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked("owner".to_string());
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+    let desk = Addr::unchecked("0xdesk".to_string());
+
+    let (factory_addr, _) = setup_factory(&mut app, &owner);
+
+    let update_denylist_msg = ExecuteMsg::UpdateDenylist {
+        add: vec![
+            stepit.to_string(),
+            not_a_scammer.to_string(),
+            desk.to_string(),
+        ],
+        remove: vec![],
+    };
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            factory_addr.clone(),
+            &update_denylist_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::Unauthorized {},
+        "expected error because only owner can update the denylist"
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        factory_addr.clone(),
+        &update_denylist_msg,
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        owner,
+        factory_addr.clone(),
+        &ExecuteMsg::UpdateDenylist {
+            add: vec![],
+            remove: vec![desk.to_string()],
+        },
+        &[],
+    )
+    .unwrap();
+
+    let resp: DenylistResponse = app
+        .wrap()
+        .query_wasm_smart(
+            factory_addr.clone(),
+            &QueryMsg::Denylist {
+                start_after: None,
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.addresses, vec![stepit.clone()]);
+
+    let resp: DenylistResponse = app
+        .wrap()
+        .query_wasm_smart(
+            factory_addr.clone(),
+            &QueryMsg::Denylist {
+                start_after: Some(stepit.to_string()),
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.addresses, vec![not_a_scammer]);
+
+    let resp: IsDeniedResponse = app
+        .wrap()
+        .query_wasm_smart(
+            factory_addr,
+            &QueryMsg::IsDenied {
+                address: desk.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp,
+        IsDeniedResponse {
+            denied: false,
+            can_withdraw: false
+        },
+        "expected removed address not to be denied"
+    );
+}
+
+#[test]
+fn market_denylist_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked("owner".to_string());
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let (factory_addr, market_addr) = setup_factory(&mut app, &owner);
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(2_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "usdc")],
+    }))
+    .unwrap();
+
+    let create_deal_msg = |counterparties: Vec<String>| MarketExecuteMsg::CreateDeal {
+        coin_out: Coin::new(1_000, "usdc"),
+        counterparties,
+        group: None,
+        expiry: Expiration::Never {},
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
+    };
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &create_deal_msg(vec![]),
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        owner.clone(),
+        factory_addr.clone(),
+        &ExecuteMsg::UpdateDenylist {
+            add: vec![stepit.to_string()],
+            remove: vec![],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // Denied addresses cannot accept deals or be reserved counterparties.
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &MarketExecuteMsg::AcceptDeal {
+                creator: not_a_scammer.to_string(),
+                deal_id: 0,
+                expected_coin_in: None,
+                min_receive: None,
+                expected_version: None,
+            },
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "0xstepit is denied");
+
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &create_deal_msg(vec![stepit.to_string()]),
+            &[Coin::new(1_000, "astro")],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "0xstepit is denied");
+
+    // Denied addresses cannot create deals nor withdraw unmatched ones unless allowed.
+    app.execute_contract(
+        owner.clone(),
+        factory_addr.clone(),
+        &ExecuteMsg::UpdateDenylist {
+            add: vec![not_a_scammer.to_string()],
+            remove: vec![],
+        },
+        &[],
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &create_deal_msg(vec![]),
+            &[Coin::new(1_000, "astro")],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "0xtrustme is denied");
+
+    // Denied addresses cannot amend or group their deals, nor deposit coins for offers.
+    for (msg, funds) in [
+        (
+            MarketExecuteMsg::AmendDeal {
+                creator: not_a_scammer.to_string(),
+                deal_id: 0,
+                new_coin_in: None,
+                new_coin_out: Some(Coin::new(900, "usdc")),
+                new_expiry: None,
+                new_counterparties: None,
+            },
+            vec![],
+        ),
+        (
+            MarketExecuteMsg::LinkOco {
+                creator: not_a_scammer.to_string(),
+                deal_ids: vec![0, 1],
+            },
+            vec![],
+        ),
+        (
+            MarketExecuteMsg::Deposit {},
+            vec![Coin::new(1_000, "astro")],
+        ),
+    ] {
+        let err = app
+            .execute_contract(not_a_scammer.clone(), market_addr.clone(), &msg, &funds)
+            .unwrap_err();
+        assert_eq!(err.root_cause().to_string(), "0xtrustme is denied");
+    }
+
+    let withdraw_msg = MarketExecuteMsg::Withdraw {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+    };
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &withdraw_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "0xtrustme is denied");
+
+    app.execute_contract(
+        owner,
+        factory_addr,
+        &ExecuteMsg::UpdateConfig {
+            new_fee_collector: None,
+            new_denied_can_withdraw: Some(true),
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(not_a_scammer.clone(), market_addr, &withdraw_msg, &[])
+        .unwrap();
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "astro")
+            .unwrap()
+            .amount
+            .u128(),
+        2_000,
+        "expected unmatched deal to be refunded"
+    );
+}
+
+#[test]
+fn settle_with_denied_party_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked("owner".to_string());
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let (factory_addr, market_addr) = setup_factory(&mut app, &owner);
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "usdc")],
+    }))
+    .unwrap();
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &MarketExecuteMsg::CreateDeal {
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::Never {},
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &MarketExecuteMsg::AcceptDeal {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+            expected_coin_in: None,
+            min_receive: None,
+            expected_version: None,
+        },
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        owner.clone(),
+        factory_addr,
+        &ExecuteMsg::UpdateDenylist {
+            add: vec![stepit.to_string()],
+            remove: vec![],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // The denied counterparty does not block the settlement for the creator.
+    app.execute_contract(
+        owner,
+        market_addr.clone(),
+        &MarketExecuteMsg::Settle {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+        },
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "usdc")
+            .unwrap()
+            .amount
+            .u128(),
+        970,
+        "expected creator paid less fee"
+    );
+    let resp: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr,
+            &MarketQueryMsg::Balance {
+                maker: stepit.to_string(),
+                denom: "astro".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp.amount.u128(),
+        970,
+        "expected denied counterparty coins credited to their balance"
+    );
+}
+
+#[test]
+fn settle_forward_with_denied_party_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked("owner".to_string());
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let (factory_addr, market_addr) = setup_factory(&mut app, &owner);
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "usdc")],
+    }))
+    .unwrap();
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &MarketExecuteMsg::CreateForward {
+            coin_in: Coin::new(1_000, "astro"),
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::Never {},
+            settle_at: Scheduled::AtHeight(app.block_info().height + 10),
+            collateral: Decimal::one(),
+        },
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &MarketExecuteMsg::AcceptForward {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+        },
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        owner.clone(),
+        factory_addr,
+        &ExecuteMsg::UpdateDenylist {
+            add: vec![stepit.to_string()],
+            remove: vec![],
+        },
+        &[],
+    )
+    .unwrap();
+    app.update_block(|block| block.height += 10);
+
+    // The denied counterparty does not block the settlement for the creator.
+    app.execute_contract(
+        owner,
+        market_addr.clone(),
+        &MarketExecuteMsg::SettleForward {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+        },
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "usdc")
+            .unwrap()
+            .amount
+            .u128(),
+        970,
+        "expected creator paid less fee"
+    );
+    let resp: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr,
+            &MarketQueryMsg::Balance {
+                maker: stepit.to_string(),
+                denom: "astro".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp.amount.u128(),
+        970,
+        "expected denied counterparty coins credited to their balance"
+    );
+}
+
+#[test]
+fn option_with_denied_writer_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked("owner".to_string());
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let (factory_addr, market_addr) = setup_factory(&mut app, &owner);
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(2_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_020, "usdc")],
+    }))
+    .unwrap();
+
+    let expiry = Expiration::AtHeight(app.block_info().height + 10);
+    for deal_id in 0..2 {
+        app.execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &MarketExecuteMsg::CreateDeal {
+                coin_out: Coin::new(1_000, "usdc"),
+                counterparties: vec![],
+                group: None,
+                expiry,
+                match_crossing: None,
+                kind: Some(DealKind::Option {
+                    premium: Coin::new(10, "usdc"),
+                }),
+                trigger: None,
+                coin_out_alternatives: vec![],
+            },
+            &[Coin::new(1_000, "astro")],
+        )
+        .unwrap();
+        app.execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &MarketExecuteMsg::BuyOption {
+                creator: not_a_scammer.to_string(),
+                deal_id,
+            },
+            &[Coin::new(10, "usdc")],
+        )
+        .unwrap();
+    }
+
+    app.execute_contract(
+        owner.clone(),
+        factory_addr.clone(),
+        &ExecuteMsg::UpdateDenylist {
+            add: vec![not_a_scammer.to_string()],
+            remove: vec![],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // The denied writer does not block the exercise of the buyer.
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &MarketExecuteMsg::ExerciseOption {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+        },
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+    let resp: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr.clone(),
+            &MarketQueryMsg::Balance {
+                maker: not_a_scammer.to_string(),
+                denom: "usdc".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp.amount.u128(),
+        970,
+        "expected denied writer coins credited to their balance"
+    );
+
+    // After expiry the denied writer is refunded of the collateral, if allowed.
+    app.update_block(|block| block.height += 10);
+    let withdraw_msg = MarketExecuteMsg::Withdraw {
+        creator: not_a_scammer.to_string(),
+        deal_id: 1,
+    };
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &withdraw_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "0xtrustme is denied");
+
+    app.execute_contract(
+        owner,
+        factory_addr,
+        &ExecuteMsg::UpdateConfig {
+            new_fee_collector: None,
+            new_denied_can_withdraw: Some(true),
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(not_a_scammer.clone(), market_addr, &withdraw_msg, &[])
+        .unwrap();
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "astro")
+            .unwrap()
+            .amount
+            .u128(),
+        1_000,
+        "expected collateral of the expired option refunded"
+    );
+}
+
+#[test]
+fn transfer_position_to_denied_address_fails() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked("owner".to_string());
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let (factory_addr, market_addr) = setup_factory(&mut app, &owner);
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &MarketExecuteMsg::CreateDeal {
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::Never {},
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        owner,
+        factory_addr,
+        &ExecuteMsg::UpdateDenylist {
+            add: vec![stepit.to_string()],
+            remove: vec![],
+        },
+        &[],
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr,
+            &MarketExecuteMsg::TransferPosition {
+                creator: not_a_scammer.to_string(),
+                deal_id: 0,
+                side: Side::Creator,
+                recipient: stepit.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "0xstepit is denied",
+        "expected error because the recipient is denied"
+    );
+}
+
+#[test]
+fn transfer_position_token_to_denied_address_fails() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked("owner".to_string());
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_id = app.store_code(market_contract());
+    let positions_id = app.store_code(positions_contract());
+    let factory_id = app.store_code(factory_contract());
+    let factory_addr = app
+        .instantiate_contract(
+            factory_id,
+            owner.clone(),
+            &InstantiateMsg {
+                owner: owner.to_string(),
+                market_code_id: market_id,
+                fee_collector: Some(owner.to_string()),
+                positions_code_id: Some(positions_id),
+                denied_can_withdraw: None,
+            },
+            &[],
+            "factory-otc",
+            None,
+        )
+        .unwrap();
+    app.execute_contract(
+        owner.clone(),
+        factory_addr.clone(),
+        &ExecuteMsg::CreateMarket {
+            first_coin: "astro".to_string(),
+            second_coin: "usdc".to_string(),
+            fee: Decimal::percent(3),
+            limits: None,
+            group: None,
+            oracle: None,
+            other_coins: None,
+            router: None,
+        },
+        &[],
+    )
+    .unwrap();
+    let resp: MarketResponse = app
+        .wrap()
+        .query_wasm_smart(
+            factory_addr.clone(),
+            &QueryMsg::Market {
+                first_denom: "astro".to_string(),
+                second_denom: "usdc".to_string(),
+            },
+        )
+        .unwrap();
+    let market_addr = Addr::unchecked(resp.address);
+    let market_config: common::market::Config = app
+        .wrap()
+        .query_wasm_smart(market_addr.clone(), &MarketQueryMsg::Config {})
+        .unwrap();
+    let positions_addr = market_config.positions.unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr,
+        &MarketExecuteMsg::CreateDeal {
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::Never {},
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        owner,
+        factory_addr,
+        &ExecuteMsg::UpdateDenylist {
+            add: vec![stepit.to_string()],
+            remove: vec![],
+        },
+        &[],
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            positions_addr.clone(),
+            &PositionsExecuteMsg::TransferNft {
+                recipient: stepit.to_string(),
+                token_id: "0-creator".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "0xstepit is denied",
+        "expected error because the recipient is denied"
+    );
+
+    // Positions not involving denied addresses are still transferable.
+    app.execute_contract(
+        not_a_scammer,
+        positions_addr,
+        &PositionsExecuteMsg::TransferNft {
+            recipient: "0xdesk".to_string(),
+            token_id: "0-creator".to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+}
@@ -0,0 +1,153 @@
+use cosmwasm_std::{Addr, Decimal, Empty};
+use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+use cw_ownable::{Action, Ownership};
+
+use crate::{
+    error::ContractError,
+    msg::{ExecuteMsg, InstantiateMsg, MarketResponse, QueryMsg},
+};
+
+use market::msg::{ExecuteMsg as MarketExecuteMsg, QueryMsg as MarketQueryMsg};
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        market::contract::execute,
+        market::contract::instantiate,
+        market::contract::query,
+    )
+    .with_reply(market::contract::reply);
+    Box::new(contract)
+}
+
+// Creates a factory contract
+pub fn factory_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    )
+    .with_reply_empty(crate::contract::reply);
+
+    Box::new(contract)
+}
+
+#[test]
+fn update_market_ownership_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked("owner".to_string());
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let desk = Addr::unchecked("0xdesk".to_string());
+
+    // Store the market contract.
+    let market_id = app.store_code(market_contract());
+
+    // Store and instantiate the factory contract.
+    let factory_id = app.store_code(factory_contract());
+    let init_factory_msg = InstantiateMsg {
+        owner: owner.to_string(),
+        market_code_id: market_id,
+        fee_collector: Some(owner.to_string()),
+        positions_code_id: None,
+        denied_can_withdraw: None,
+    };
+    let factory_addr = app
+        .instantiate_contract(
+            factory_id,
+            owner.clone(),
+            &init_factory_msg,
+            &[],
+            "factory-otc",
+            None,
+        )
+        .unwrap();
+
+    let create_market_msg = ExecuteMsg::CreateMarket {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(3),
+        limits: None,
+        group: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    app.execute_contract(owner.clone(), factory_addr.clone(), &create_market_msg, &[])
+        .unwrap();
+
+    let resp_market: MarketResponse = app
+        .wrap()
+        .query_wasm_smart(
+            factory_addr.clone(),
+            &QueryMsg::Market {
+                first_denom: "astro".to_string(),
+                second_denom: "usdc".to_string(),
+            },
+        )
+        .unwrap();
+    let ownership: Ownership<Addr> = app
+        .wrap()
+        .query_wasm_smart(resp_market.address.clone(), &MarketQueryMsg::Ownership {})
+        .unwrap();
+    assert_eq!(
+        ownership.owner,
+        Some(factory_addr.clone()),
+        "expected market owned by the factory"
+    );
+
+    let transfer_msg = ExecuteMsg::UpdateMarketOwnership {
+        first_coin: "usdc".to_string(),
+        second_coin: "astro".to_string(),
+        action: Action::TransferOwnership {
+            new_owner: desk.to_string(),
+            expiry: None,
+        },
+    };
+    let err = app
+        .execute_contract(stepit, factory_addr.clone(), &transfer_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::Unauthorized {},
+        "expected error because stepit is not owner"
+    );
+
+    app.execute_contract(owner.clone(), factory_addr.clone(), &transfer_msg, &[])
+        .unwrap();
+    app.execute_contract(
+        desk.clone(),
+        Addr::unchecked(resp_market.address.clone()),
+        &MarketExecuteMsg::UpdateOwnership(Action::AcceptOwnership),
+        &[],
+    )
+    .unwrap();
+
+    let ownership: Ownership<Addr> = app
+        .wrap()
+        .query_wasm_smart(resp_market.address, &MarketQueryMsg::Ownership {})
+        .unwrap();
+    assert_eq!(
+        ownership.owner,
+        Some(desk),
+        "expected market ownership transferred"
+    );
+
+    let err = app
+        .execute_contract(
+            owner,
+            factory_addr,
+            &ExecuteMsg::UpdateMarketOwnership {
+                first_coin: "astro".to_string(),
+                second_coin: "uluna".to_string(),
+                action: Action::RenounceOwnership,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::MarketNotFound {},
+        "expected error because market does not exist"
+    );
+}
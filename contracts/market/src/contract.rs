@@ -1,7 +1,8 @@
 use cosmwasm_std::{
-    coin, entry_point, to_json_binary, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult,
+    coin, entry_point, to_json_binary, to_json_vec, Binary, Decimal, Deps, DepsMut, Env,
+    MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, Storage, SubMsg, WasmMsg,
 };
+use sha2::{Digest, Sha256};
 
 use crate::{
     error::ContractError,
@@ -11,7 +12,8 @@ use crate::{
 
 use astroport::asset::validate_native_denom;
 
-use common::market::{Config, InstantiateMsg};
+use common::market::{Config, InstantiateMsg, Offer};
+use positions::msg::InstantiateMsg as PositionsInstantiateMsg;
 
 const CONTRACT_NAME: &str = "crates.io/cw-otc-market";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -19,10 +21,23 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 // Maximum allowed fee is 5%.
 pub const MAX_FEE: Decimal = Decimal::percent(5);
 
+/// Maximum number of counterparties allowed to accept a deal.
+pub const MAX_COUNTERPARTIES: usize = 10;
+
+/// Number of price levels returned for each side of the order book if not specified.
+pub const DEFAULT_ORDER_BOOK_DEPTH: u32 = 10;
+/// Maximum number of price levels returned for each side of the order book.
+pub const MAX_ORDER_BOOK_DEPTH: u32 = 100;
+
+/// ID of the reply call expected from the positions contract creation.
+const INSTANTIATE_POSITIONS_REPLY_ID: u64 = 1;
+/// ID of the reply call expected from the swap of a deal accepted with a different coin.
+const SWAP_REPLY_ID: u64 = 2;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
@@ -36,21 +51,78 @@ pub fn instantiate(
             second_coin: msg.second_coin,
         });
     }
+    let other_coins = msg.other_coins.unwrap_or_default();
+    for (index, denom) in other_coins.iter().enumerate() {
+        validate_native_denom(denom)?;
+        if *denom == msg.first_coin
+            || *denom == msg.second_coin
+            || other_coins[..index].contains(denom)
+        {
+            return Err(ContractError::DuplicatedCoin {
+                denom: denom.clone(),
+            });
+        }
+    }
     if msg.fee > MAX_FEE {
         return Err(ContractError::OverFeeMax {});
     };
 
-    CONFIG.save(
-        deps.storage,
-        &Config {
-            owner: info.sender,
-            first_coin: msg.first_coin,
-            second_coin: msg.second_coin,
-            fee: msg.fee,
-        },
-    )?;
+    cw_ownable::initialize_owner(deps.storage, deps.api, Some(info.sender.as_str()))?;
+
+    let config = Config {
+        first_coin: msg.first_coin,
+        second_coin: msg.second_coin,
+        fee: msg.fee,
+        positions: None,
+        limits: msg.limits.unwrap_or_default(),
+        group: msg
+            .group
+            .map(|group| deps.api.addr_validate(&group))
+            .transpose()?,
+        factory: msg
+            .factory
+            .map(|factory| deps.api.addr_validate(&factory))
+            .transpose()?,
+        oracle: msg
+            .oracle
+            .map(|oracle| deps.api.addr_validate(&oracle))
+            .transpose()?,
+        other_coins,
+        router: msg
+            .router
+            .map(|router| deps.api.addr_validate(&router))
+            .transpose()?,
+    };
+    execute::check_valid_limits(&config.limits, &config)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    // The market is the minter of the positions contract.
+    let sub_msgs: Vec<SubMsg> = msg
+        .positions_code_id
+        .map(|code_id| -> StdResult<SubMsg> {
+            Ok(SubMsg {
+                id: INSTANTIATE_POSITIONS_REPLY_ID,
+                msg: WasmMsg::Instantiate {
+                    admin: None,
+                    code_id,
+                    msg: to_json_binary(&PositionsInstantiateMsg {
+                        name: "OTC deal positions".to_string(),
+                        symbol: "OTCPOS".to_string(),
+                        minter: env.contract.address.to_string(),
+                    })?,
+                    funds: vec![],
+                    label: "Market positions".to_string(),
+                }
+                .into(),
+                gas_limit: None,
+                reply_on: ReplyOn::Success,
+            })
+        })
+        .transpose()?
+        .into_iter()
+        .collect();
 
-    Ok(Response::new())
+    Ok(Response::new().add_submessages(sub_msgs))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -64,11 +136,158 @@ pub fn execute(
     match msg {
         CreateDeal {
             coin_out,
-            counterparty,
-            timeout,
-        } => execute::create_deal(deps, env, info, coin_out, counterparty, timeout),
-        AcceptDeal { creator, deal_id } => execute::accept_deal(deps, info, env, creator, deal_id),
+            counterparties,
+            group,
+            expiry,
+            match_crossing,
+            kind,
+            trigger,
+            coin_out_alternatives,
+        } => execute::create_deal(
+            deps,
+            env,
+            info,
+            coin_out,
+            counterparties,
+            group,
+            expiry,
+            match_crossing.unwrap_or_default(),
+            kind.unwrap_or_default(),
+            trigger,
+            coin_out_alternatives,
+        ),
+        AcceptDeal {
+            creator,
+            deal_id,
+            expected_coin_in,
+            min_receive,
+            expected_version,
+        } => execute::accept_deal(
+            deps,
+            info,
+            env,
+            creator,
+            deal_id,
+            expected_coin_in,
+            min_receive,
+            expected_version,
+        ),
+        AcceptDealWithSwap {
+            creator,
+            deal_id,
+            max_spread,
+            expected_coin_in,
+            min_receive,
+            expected_version,
+        } => execute::accept_deal_with_swap(
+            deps,
+            info,
+            env,
+            creator,
+            deal_id,
+            max_spread,
+            expected_coin_in,
+            min_receive,
+            expected_version,
+        ),
+        LinkOco { creator, deal_ids } => execute::link_oco(deps, info, creator, deal_ids),
+        AcceptDealFor {
+            taker,
+            creator,
+            deal_id,
+            expected_coin_in,
+            min_receive,
+            expected_version,
+        } => execute::accept_deal_for(
+            deps,
+            info,
+            env,
+            taker,
+            creator,
+            deal_id,
+            expected_coin_in,
+            min_receive,
+            expected_version,
+        ),
         Withdraw { creator, deal_id } => execute::withdraw(deps, info, env, creator, deal_id),
+        Deposit {} => execute::deposit(deps, info),
+        WithdrawBalance { coin } => execute::withdraw_balance(deps, info, coin),
+        SetOfferSigner { pubkey } => execute::set_offer_signer(deps, info, pubkey),
+        AcceptOffer { offer, signature } => {
+            execute::accept_offer(deps, info, env, offer, signature)
+        }
+        CancelOffers { nonces } => execute::cancel_offers(deps, info, nonces),
+        BatchCreateDeals { deals } => execute::batch_create_deals(deps, env, info, deals),
+        BatchAcceptDeals { deals } => execute::batch_accept_deals(deps, env, info, deals),
+        BatchWithdraw { deals } => execute::batch_withdraw(deps, info, env, deals),
+        Settle { creator, deal_id } => execute::settle(deps, info, env, creator, deal_id),
+        TransferPosition {
+            creator,
+            deal_id,
+            side,
+            recipient,
+        } => execute::transfer_position(deps, info, env, creator, deal_id, side, recipient),
+        MarketTake {
+            max_price,
+            min_receive,
+            denom_out,
+        } => execute::market_take(deps, info, env, max_price, min_receive, denom_out),
+        AmendDeal {
+            creator,
+            deal_id,
+            new_coin_in,
+            new_coin_out,
+            new_expiry,
+            new_counterparties,
+        } => execute::amend_deal(
+            deps,
+            info,
+            env,
+            creator,
+            deal_id,
+            new_coin_in,
+            new_coin_out,
+            new_expiry,
+            new_counterparties,
+        ),
+        UpdateLimits { limits } => execute::update_limits(deps, info, limits),
+        BuyOption { creator, deal_id } => execute::buy_option(deps, info, env, creator, deal_id),
+        ExerciseOption { creator, deal_id } => {
+            execute::exercise_option(deps, info, env, creator, deal_id)
+        }
+        CreateForward {
+            coin_in,
+            coin_out,
+            counterparties,
+            group,
+            expiry,
+            settle_at,
+            collateral,
+        } => execute::create_forward(
+            deps,
+            env,
+            info,
+            coin_in,
+            coin_out,
+            counterparties,
+            group,
+            expiry,
+            settle_at,
+            collateral,
+        ),
+        AcceptForward { creator, deal_id } => {
+            execute::accept_forward(deps, info, env, creator, deal_id)
+        }
+        FundForward { creator, deal_id } => {
+            execute::fund_forward(deps, info, env, creator, deal_id)
+        }
+        SettleForward { creator, deal_id } => {
+            execute::settle_forward(deps, info, env, creator, deal_id)
+        }
+        AcceptTranche { creator, deal_id } => {
+            execute::accept_tranche(deps, info, env, creator, deal_id)
+        }
+        UpdateOwnership(action) => execute::update_ownership(deps, env, info, action),
     }
 }
 
@@ -81,189 +300,2697 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_json_binary(&query::get_deals_by_creator(deps, env, creator)?)
         }
         AllDeals {} => to_json_binary(&query::get_all_deals(deps, env)?),
+        OrderBook {
+            depth,
+            first_denom,
+            second_denom,
+        } => to_json_binary(&query::get_order_book(
+            deps,
+            env,
+            depth,
+            first_denom,
+            second_denom,
+        )?),
+        Balance { maker, denom } => to_json_binary(&query::get_balance(deps, maker, denom)?),
+        OfferSigner { maker } => to_json_binary(&query::get_offer_signer(deps, maker)?),
+        NonceUsed { maker, nonce } => to_json_binary(&query::get_nonce_used(deps, maker, nonce)?),
+        OfferSignBytes { offer } => to_json_binary(&offer_sign_bytes(&offer)?),
+        OcoGroup { group_id } => to_json_binary(&query::get_oco_group(deps, group_id)?),
+        Ownership {} => to_json_binary(&cw_ownable::get_ownership(deps.storage)?),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        INSTANTIATE_POSITIONS_REPLY_ID => {
+            let res = cw_utils::parse_reply_instantiate_data(msg).map_err(|_| {
+                StdError::parse_err("MsgInstantiateContractResponse", "failed to parse data")
+            })?;
+            reply::handle_instantiate_reply(deps, res)
+        }
+        SWAP_REPLY_ID => reply::handle_swap_reply(deps, env),
+        _ => Err(ContractError::UnknownReply {}),
     }
 }
 
 pub mod execute {
-    use std::ops::Add;
+    use std::{cmp::Ordering, collections::BTreeMap};
 
-    use common::market::{Deal, DealStatus, WithdrawStatus};
-    use cosmwasm_std::{Addr, BankMsg, Coin, CosmosMsg, Uint128};
+    use astroport::{
+        asset::AssetInfo,
+        router::{ExecuteMsg as RouterExecuteMsg, SwapOperation},
+    };
+    use common::{
+        factory::{DenylistQueryMsg, IsDeniedResponse},
+        market::{
+            Deal, DealKind, DealLimits, DealStatus, Offer, PriceTrigger, Side, WithdrawStatus,
+        },
+        oracle::{OracleQueryMsg, PriceResponse},
+    };
+    use cosmwasm_std::{Addr, BankMsg, Coin, CosmosMsg, Empty, Event, Order, Uint128};
+    use cw4::Cw4Contract;
+    use cw721::OwnerOfResponse;
+    use cw_utils::{Duration, Expiration, Scheduled};
+    use positions::msg::{
+        ExecuteMsg as PositionsExecuteMsg, PositionsExecuteMsg as PositionsExtensionMsg,
+        QueryMsg as PositionsQueryMsg,
+    };
 
-    use crate::state::{next_id, DEALS};
+    use crate::{
+        msg::{BatchResponse, DealKey, DealParams, DealResult},
+        state::{
+            book_price, deals_map, next_id, next_oco_id, PendingSwap, BALANCES, OFFER_SIGNERS,
+            PENDING_SWAP, USED_NONCES,
+        },
+    };
 
     use super::*;
 
-    /// Crerate a new deal. The deal can be open of specific for one counterparty.
+    /// Crerate a new deal. The deal can be open of specific for a list of counterparties or the
+    /// members of a group. An open deal can be first matched against crossing deals, with only the
+    /// amount left stored as a new deal.
+    #[allow(clippy::too_many_arguments)]
     pub fn create_deal(
-        deps: DepsMut,
+        mut deps: DepsMut,
         env: Env,
         info: MessageInfo,
         coin_out: Coin,
-        counterparty: Option<String>,
-        timeout: u64,
+        counterparties: Vec<String>,
+        group: Option<String>,
+        expiry: Expiration,
+        match_crossing: bool,
+        kind: DealKind,
+        trigger: Option<PriceTrigger>,
+        coin_out_alternatives: Vec<Coin>,
     ) -> Result<Response, ContractError> {
         let config = CONFIG.load(deps.storage)?;
 
+        check_group_member(deps.as_ref(), &config.group, &info.sender)?;
+        check_not_denied(deps.as_ref(), &config, &info.sender)?;
+
         check_only_one_coin(&info.funds)?;
-        check_allowed_coin(&info.funds[0].denom, &config)?;
-        check_allowed_coin(&coin_out.denom, &config)?;
+        let coin_in = info.funds[0].clone();
 
-        let counterparty = counterparty
-            .as_ref()
-            .map(|addr| deps.api.addr_validate(addr))
-            .transpose()?;
+        // Forwards lock only a fraction of the offered coin.
+        if matches!(kind, DealKind::Forward { .. }) {
+            return Err(ContractError::WrongDealKind {});
+        }
 
-        let deal = Deal {
-            coin_in: info.funds[0].clone(),
-            coin_out,
-            counterparty,
-            timeout: env.block.height.add(timeout),
-            status: DealStatus::NotMatched,
+        let mut response = Response::new()
+            .add_attribute("action", "create_dial")
+            .add_attribute("creator", info.sender.clone());
+
+        let (coin_in, coin_out) = if match_crossing
+            && counterparties.is_empty()
+            && group.is_none()
+            && kind == DealKind::Spot
+            && trigger.is_none()
+            && coin_out_alternatives.is_empty()
+        {
+            check_allowed_coin(&coin_in.denom, &config)?;
+            check_allowed_coin(&coin_out.denom, &config)?;
+            check_expiry(&expiry, &env)?;
+            check_duration_limits(&config.limits, &expiry, &env)?;
+            check_amount_limits(&config.limits, &coin_in)?;
+            check_amount_limits(&config.limits, &coin_out)?;
+            let (left_in, left_out, msgs) = cross_deal(
+                deps.branch(),
+                &env,
+                &config,
+                &info.sender,
+                coin_in,
+                coin_out,
+            )?;
+            response = response.add_messages(msgs);
+            if left_in.amount.is_zero() {
+                return Ok(response);
+            }
+            // Too little is left to be exchanged at the requested price or to be a deal.
+            if left_out.amount.is_zero()
+                || check_amount_limits(&config.limits, &left_in).is_err()
+                || check_amount_limits(&config.limits, &left_out).is_err()
+            {
+                return Ok(
+                    response.add_messages(create_withdraw_msg_not_matched(info.sender, left_in))
+                );
+            }
+            (left_in, left_out)
+        } else {
+            (coin_in, coin_out)
         };
 
-        let deal_id = next_id(deps.storage)?;
-        DEALS.save(deps.storage, (&info.sender, deal_id), &deal)?;
+        let (deal_id, msgs) = save_new_deal(
+            deps,
+            &env,
+            &config,
+            &info.sender,
+            coin_in,
+            coin_out,
+            counterparties,
+            group,
+            expiry,
+            kind,
+            trigger,
+            coin_out_alternatives,
+        )?;
+
+        Ok(response
+            .add_attribute("deal_id", deal_id.to_string())
+            .add_messages(msgs))
+    }
+
+    /// Match a new deal against the open deals offering its requested coin with a price equal or
+    /// better than its own, in price and creation order. Returns the coins left for the new deal,
+    /// keeping its price, and the messages paying the matched creators and the sender.
+    pub fn cross_deal(
+        mut deps: DepsMut,
+        env: &Env,
+        config: &Config,
+        sender: &Addr,
+        coin_in: Coin,
+        coin_out: Coin,
+    ) -> Result<(Coin, Coin, Vec<CosmosMsg>), ContractError> {
+        let max_price = Decimal::checked_from_ratio(coin_in.amount, coin_out.amount)
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
+        let (spent, received, mut msgs) = fill_from_book(
+            deps.branch(),
+            env,
+            config,
+            sender,
+            &coin_in,
+            &coin_out.denom,
+            max_price,
+        )?;
+
+        if !received.is_zero() {
+            let fee_recipient = cw_ownable::get_ownership(deps.storage)?.owner;
+            msgs.extend(create_withdraw_msg_matched(
+                sender.clone(),
+                coin(received.u128(), &coin_out.denom),
+                config.fee,
+                &fee_recipient,
+            ));
+        }
+
+        let left_in = coin_in.amount - spent;
+        let left_out = coin_out.amount.multiply_ratio(left_in, coin_in.amount);
+        Ok((
+            coin(left_in.u128(), coin_in.denom),
+            coin(left_out.u128(), coin_out.denom),
+            msgs,
+        ))
+    }
+
+    /// Allows the holder of the creator position to amend a deal not yet matched keeping its
+    /// identifier. The offered amount can be increased by sending the difference, or decreased
+    /// receiving back the difference.
+    #[allow(clippy::too_many_arguments)]
+    pub fn amend_deal(
+        deps: DepsMut,
+        info: MessageInfo,
+        env: Env,
+        creator: String,
+        deal_id: u64,
+        new_coin_in: Option<Uint128>,
+        new_coin_out: Option<Coin>,
+        new_expiry: Option<Expiration>,
+        new_counterparties: Option<Vec<String>>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let creator = Addr::unchecked(creator);
+
+        check_not_denied(deps.as_ref(), &config, &info.sender)?;
+        let mut deal = deals_map().load(deps.storage, (&creator, deal_id))?;
+        let creator_holder = position_holder(
+            deps.as_ref(),
+            &config,
+            &creator,
+            deal_id,
+            &deal,
+            &Side::Creator,
+        )?;
+        if Some(&info.sender) != creator_holder.as_ref() {
+            return Err(ContractError::Unauthorized {});
+        }
+        if deal.status != DealStatus::NotMatched {
+            return Err(ContractError::DealNotAvailable {});
+        }
+        if matches!(deal.kind, DealKind::Forward { .. }) {
+            return Err(ContractError::WrongDealKind {});
+        }
+
+        let mut msgs = vec![];
+        let mut top_up = None;
+        if let Some(amount) = new_coin_in {
+            if amount.is_zero() {
+                return Err(ContractError::ZeroAmount {});
+            }
+            if amount > deal.coin_in.amount {
+                top_up = Some(coin(
+                    (amount - deal.coin_in.amount).u128(),
+                    &deal.coin_in.denom,
+                ));
+            } else if amount < deal.coin_in.amount {
+                msgs.extend(create_withdraw_msg_not_matched(
+                    info.sender.clone(),
+                    coin((deal.coin_in.amount - amount).u128(), &deal.coin_in.denom),
+                ));
+            }
+            // Alternatives keep the same ratio with the new coin deposited in the deal.
+            for alternative in deal.coin_out_alternatives.iter_mut() {
+                alternative.amount = alternative
+                    .amount
+                    .multiply_ratio(amount, deal.coin_in.amount);
+            }
+            deal.coin_in.amount = amount;
+        }
+        check_funds_match(&info.funds, top_up.into_iter())?;
+
+        if let Some(coin_out) = new_coin_out {
+            check_allowed_coin(&coin_out.denom, &config)?;
+            if coin_out.amount.is_zero() {
+                return Err(ContractError::ZeroAmount {});
+            }
+            // Alternatives were priced against the previous requested coin.
+            deal.coin_out = coin_out;
+            deal.coin_out_alternatives = vec![];
+        }
+        if let Some(expiry) = new_expiry {
+            check_expiry(&expiry, &env)?;
+            check_duration_limits(&config.limits, &expiry, &env)?;
+            deal.expiry = expiry;
+        }
+        if let Some(counterparties) = new_counterparties {
+            if deal.group.is_some() && !counterparties.is_empty() {
+                return Err(ContractError::CounterpartiesAndGroup {});
+            }
+            deal.counterparties = validate_counterparties(deps.as_ref(), counterparties)?;
+            for counterparty in &deal.counterparties {
+                check_not_denied(deps.as_ref(), &config, counterparty)?;
+            }
+        }
+        check_amount_limits(&config.limits, &deal.coin_in)?;
+        check_amount_limits(&config.limits, &deal.coin_out)?;
+        deal.version += 1;
+
+        deals_map().save(deps.storage, (&creator, deal_id), &deal)?;
 
         Ok(Response::new()
-            .add_attribute("action", "create_dial")
+            .add_attribute("action", "amend_deal")
             .add_attribute("deal_id", deal_id.to_string())
-            .add_attribute("creator", info.sender))
+            .add_attribute("version", deal.version.to_string())
+            .add_messages(msgs))
     }
 
     // To allow an address to accept a deal, we have to check the following conditions:
     // 1. deal has not ben previously matched and is not expired.
     // 2. sent funds are the same requested by the creator of the deal.
     // 3. if the deal is associated with an address, sender must be that address
+    // 4. if specified, deal terms are the ones expected by the sender.
+    #[allow(clippy::too_many_arguments)]
     pub fn accept_deal(
         deps: DepsMut,
         info: MessageInfo,
         env: Env,
         creator: String,
         deal_id: u64,
+        expected_coin_in: Option<Coin>,
+        min_receive: Option<Uint128>,
+        expected_version: Option<u64>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        check_only_one_coin(&info.funds)?;
+
+        let fee_recipient = cw_ownable::get_ownership(deps.storage)?.owner;
+        let creator = Addr::unchecked(creator);
+        let (deal, msgs) = match_deal(
+            deps,
+            &env,
+            &config,
+            &info.sender,
+            &creator,
+            deal_id,
+            &info.funds[0],
+        )?;
+
+        check_deal_terms(
+            &deal,
+            &config,
+            &fee_recipient,
+            expected_coin_in,
+            min_receive,
+            expected_version,
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_deal")
+            .add_attribute("deal_counterparty", deal.counterparty.unwrap())
+            .add_messages(msgs))
+    }
+
+    /// Swap the coin sent by the taker into the coin requested by the deal through the market
+    /// router. The deal is accepted in the reply of the swap.
+    #[allow(clippy::too_many_arguments)]
+    pub fn accept_deal_with_swap(
+        deps: DepsMut,
+        info: MessageInfo,
+        env: Env,
+        creator: String,
+        deal_id: u64,
+        max_spread: Option<Decimal>,
+        expected_coin_in: Option<Coin>,
+        min_receive: Option<Uint128>,
+        expected_version: Option<u64>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let router = config
+            .router
+            .clone()
+            .ok_or(ContractError::MissingRouter {})?;
+
+        check_only_one_coin(&info.funds)?;
+
+        let fee_recipient = cw_ownable::get_ownership(deps.storage)?.owner;
+        let creator = Addr::unchecked(creator);
+        let deal = deals_map().load(deps.storage, (&creator, deal_id))?;
+        if deal.kind != DealKind::Spot {
+            return Err(ContractError::WrongDealKind {});
+        }
+        check_can_accept(
+            deps.as_ref(),
+            &env,
+            &config,
+            &info.sender,
+            &creator,
+            deal_id,
+            &deal,
+        )?;
+        check_deal_terms(
+            &deal,
+            &config,
+            &fee_recipient,
+            expected_coin_in,
+            min_receive,
+            expected_version,
+        )?;
+
+        let offer_coin = info.funds[0].clone();
+        if offer_coin.denom == deal.coin_out.denom {
+            return Err(ContractError::SwapNotRequired {});
+        }
+
+        // The requested coin received from the swap is computed from the balance of the market.
+        let balance = deps
+            .querier
+            .query_balance(&env.contract.address, &deal.coin_out.denom)?
+            .amount;
+        PENDING_SWAP.save(
+            deps.storage,
+            &PendingSwap {
+                taker: info.sender.clone(),
+                creator,
+                deal_id,
+                balance,
+            },
+        )?;
+
+        let swap_msg = WasmMsg::Execute {
+            contract_addr: router.to_string(),
+            msg: to_json_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                operations: vec![SwapOperation::AstroSwap {
+                    offer_asset_info: AssetInfo::NativeToken {
+                        denom: offer_coin.denom.clone(),
+                    },
+                    ask_asset_info: AssetInfo::NativeToken {
+                        denom: deal.coin_out.denom.clone(),
+                    },
+                }],
+                minimum_receive: Some(deal.coin_out.amount),
+                to: None,
+                max_spread,
+            })?,
+            funds: vec![offer_coin.clone()],
+        };
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_deal_with_swap")
+            .add_attribute("offer_coin", offer_coin.to_string())
+            .add_submessage(SubMsg::reply_on_success(swap_msg, SWAP_REPLY_ID)))
+    }
+
+    /// Group open deals of a creator held by the sender in a new OCO group, so that when one of
+    /// them is accepted the others are cancelled.
+    pub fn link_oco(
+        deps: DepsMut,
+        info: MessageInfo,
+        creator: String,
+        deal_ids: Vec<u64>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let creator = Addr::unchecked(creator);
+
+        check_not_denied(deps.as_ref(), &config, &info.sender)?;
+        if deal_ids.len() < 2 {
+            return Err(ContractError::InvalidOcoGroup {});
+        }
+
+        let oco = next_oco_id(deps.storage)?;
+        for deal_id in &deal_ids {
+            let mut deal = deals_map().load(deps.storage, (&creator, *deal_id))?;
+            let creator_holder = position_holder(
+                deps.as_ref(),
+                &config,
+                &creator,
+                *deal_id,
+                &deal,
+                &Side::Creator,
+            )?;
+            if Some(&info.sender) != creator_holder.as_ref() {
+                return Err(ContractError::Unauthorized {});
+            }
+            if deal.oco.is_some()
+                || !matches!(
+                    deal.status,
+                    DealStatus::NotMatched | DealStatus::Recurring { .. }
+                )
+            {
+                return Err(ContractError::InvalidOcoGroup {});
+            }
+            deal.oco = Some(oco);
+            deals_map().save(deps.storage, (&creator, *deal_id), &deal)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "link_oco")
+            .add_attribute("oco_group", oco.to_string())
+            .add_attribute("deals", deal_ids.len().to_string()))
+    }
+
+    /// Allows the factory of the market to accept a deal on behalf of the taker, with the coin
+    /// sent by the factory.
+    #[allow(clippy::too_many_arguments)]
+    pub fn accept_deal_for(
+        deps: DepsMut,
+        info: MessageInfo,
+        env: Env,
+        taker: String,
+        creator: String,
+        deal_id: u64,
+        expected_coin_in: Option<Coin>,
+        min_receive: Option<Uint128>,
+        expected_version: Option<u64>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        if config.factory.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let info = MessageInfo {
+            sender: deps.api.addr_validate(&taker)?,
+            funds: info.funds,
+        };
+        accept_deal(
+            deps,
+            info,
+            env,
+            creator,
+            deal_id,
+            expected_coin_in,
+            min_receive,
+            expected_version,
+        )
+    }
+
+    /// Allows to withdraw tokens asscoiated with a deal. If no one accepted the deal, the creator can
+    /// close it and withdraw coins without deducted fee. If a deal is close, fee are deducted from
+    /// both the parties.
+    pub fn withdraw(
+        deps: DepsMut,
+        info: MessageInfo,
+        env: Env,
+        creator: String,
+        deal_id: u64,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        let creator = Addr::unchecked(creator);
+        let (_, msgs) = withdraw_deal(deps, &env, &config, &info.sender, &creator, deal_id)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "withdraw")
+            .add_messages(msgs))
+    }
+
+    /// Allows anyone to complete a matched deal by sending the remaining coins to both the
+    /// parties. Fee are deducted as in a withdraw.
+    pub fn settle(
+        deps: DepsMut,
+        info: MessageInfo,
+        _env: Env,
+        creator: String,
+        deal_id: u64,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        let creator = Addr::unchecked(creator);
+        let msgs = settle_deal(deps, &config, &creator, deal_id)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "settle")
+            .add_attribute("deal_id", deal_id.to_string())
+            .add_attribute("settler", info.sender)
+            .add_messages(msgs))
+    }
+
+    /// Allows to buy the market coin not sent from the cheapest open deals, filling them fully or
+    /// partially until the sent coin is spent. The amount not spent is refunded to the sender.
+    pub fn market_take(
+        mut deps: DepsMut,
+        info: MessageInfo,
+        env: Env,
+        max_price: Decimal,
+        min_receive: Uint128,
+        denom_out: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        check_group_member(deps.as_ref(), &config.group, &info.sender)?;
+        check_not_denied(deps.as_ref(), &config, &info.sender)?;
+        check_only_one_coin(&info.funds)?;
+        let sent_coin = info.funds[0].clone();
+        check_allowed_coin(&sent_coin.denom, &config)?;
+        let denom_out = counter_denom(&sent_coin.denom, denom_out, &config)?;
+
+        let (spent, received, mut msgs) = fill_from_book(
+            deps.branch(),
+            &env,
+            &config,
+            &info.sender,
+            &sent_coin,
+            &denom_out,
+            max_price,
+        )?;
+
+        let fee_recipient = cw_ownable::get_ownership(deps.storage)?.owner;
+        let received_after_fee = amount_after_fee(received, config.fee, &fee_recipient);
+        if received_after_fee < min_receive {
+            return Err(ContractError::MinReceiveNotMet {
+                received: received_after_fee,
+                min_receive,
+            });
+        }
+
+        if !received.is_zero() {
+            msgs.extend(create_withdraw_msg_matched(
+                info.sender.clone(),
+                coin(received.u128(), &denom_out),
+                config.fee,
+                &fee_recipient,
+            ));
+        }
+        let refund = sent_coin.amount - spent;
+        if !refund.is_zero() {
+            msgs.extend(create_withdraw_msg_not_matched(
+                info.sender.clone(),
+                coin(refund.u128(), &sent_coin.denom),
+            ));
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "market_take")
+            .add_attribute("taker", info.sender)
+            .add_attribute("spent", coin(spent.u128(), sent_coin.denom).to_string())
+            .add_attribute("received", coin(received.u128(), denom_out).to_string())
+            .add_messages(msgs))
+    }
+
+    /// Allows to buy an option deal paying its premium, which is sent to the creator immediately.
+    pub fn buy_option(
+        mut deps: DepsMut,
+        info: MessageInfo,
+        env: Env,
+        creator: String,
+        deal_id: u64,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        check_only_one_coin(&info.funds)?;
+
+        let creator = Addr::unchecked(creator);
+        let mut deal = deals_map().load(deps.storage, (&creator, deal_id))?;
+        let DealKind::Option { premium } = deal.kind.clone() else {
+            return Err(ContractError::WrongDealKind {});
+        };
+        let creator_holder = check_can_accept(
+            deps.as_ref(),
+            &env,
+            &config,
+            &info.sender,
+            &creator,
+            deal_id,
+            &deal,
+        )?;
+
+        if info.funds[0] != premium {
+            return Err(ContractError::WrongCoin {
+                denom: premium.denom,
+                amount: premium.amount,
+            });
+        }
+
+        deal.counterparty = Some(info.sender.clone());
+        deal.status = DealStatus::OptionBought;
+        deals_map().save(deps.storage, (&creator, deal_id), &deal)?;
+        let oco_msgs = cancel_oco_group(deps.branch(), &config, deal_id, &deal)?;
+
+        let fee_recipient = cw_ownable::get_ownership(deps.storage)?.owner;
+        let mut msgs =
+            create_withdraw_msg_matched(creator_holder, premium, config.fee, &fee_recipient);
+        msgs.extend(create_mint_position_msgs(
+            &config,
+            deal_id,
+            Side::Counterparty,
+            &info.sender,
+        )?);
+        msgs.extend(oco_msgs);
+
+        Ok(Response::new()
+            .add_attribute("action", "buy_option")
+            .add_attribute("deal_id", deal_id.to_string())
+            .add_attribute("deal_counterparty", info.sender)
+            .add_messages(msgs))
+    }
+
+    /// Allows the holder of a bought option to exercise it before expiry paying the requested
+    /// coin. Both the parties receive the coins of the other less the market fee.
+    pub fn exercise_option(
+        mut deps: DepsMut,
+        info: MessageInfo,
+        env: Env,
+        creator: String,
+        deal_id: u64,
     ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
         check_only_one_coin(&info.funds)?;
 
-        let creator = Addr::unchecked(creator);
-        if info.sender == creator {
-            return Err(ContractError::SenderIsCreator {});
+        let creator = Addr::unchecked(creator);
+        let deal = deals_map().load(deps.storage, (&creator, deal_id))?;
+        if !matches!(deal.kind, DealKind::Option { .. }) {
+            return Err(ContractError::WrongDealKind {});
+        }
+        if deal.status != DealStatus::OptionBought || deal.expiry.is_expired(&env.block) {
+            return Err(ContractError::DealNotAvailable {});
+        }
+
+        let holder = position_holder(
+            deps.as_ref(),
+            &config,
+            &creator,
+            deal_id,
+            &deal,
+            &Side::Counterparty,
+        )?;
+        if Some(&info.sender) != holder.as_ref() {
+            return Err(ContractError::Unauthorized {});
+        }
+        check_not_denied(deps.as_ref(), &config, &info.sender)?;
+
+        if info.funds[0] != deal.coin_out {
+            return Err(ContractError::WrongCoin {
+                denom: deal.coin_out.denom,
+                amount: deal.coin_out.amount,
+            });
+        }
+
+        let creator_holder = position_holder(
+            deps.as_ref(),
+            &config,
+            &creator,
+            deal_id,
+            &deal,
+            &Side::Creator,
+        )?
+        .unwrap();
+
+        // A denied writer does not block the exercise, their coin is credited to their balance.
+        let fee_recipient = cw_ownable::get_ownership(deps.storage)?.owner;
+        let mut msgs = pay_or_credit(
+            deps.branch(),
+            &config,
+            creator_holder,
+            deal.coin_out.clone(),
+            config.fee,
+            &fee_recipient,
+        )?;
+        msgs.extend(create_withdraw_msg_matched(
+            info.sender.clone(),
+            deal.coin_in.clone(),
+            config.fee,
+            &fee_recipient,
+        ));
+        msgs.extend(create_burn_position_msgs(&config, deal_id, Side::Creator)?);
+        msgs.extend(create_burn_position_msgs(
+            &config,
+            deal_id,
+            Side::Counterparty,
+        )?);
+
+        deals_map().remove(deps.storage, (&creator, deal_id))?;
+
+        Ok(Response::new()
+            .add_attribute("action", "exercise_option")
+            .add_attribute("deal_id", deal_id.to_string())
+            .add_attribute("exerciser", info.sender)
+            .add_messages(msgs))
+    }
+
+    /// Create a forward deal depositing the collateral of the offered coin.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_forward(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        coin_in: Coin,
+        coin_out: Coin,
+        counterparties: Vec<String>,
+        group: Option<String>,
+        expiry: Expiration,
+        settle_at: Scheduled,
+        collateral: Decimal,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        check_group_member(deps.as_ref(), &config.group, &info.sender)?;
+        check_not_denied(deps.as_ref(), &config, &info.sender)?;
+        check_only_one_coin(&info.funds)?;
+
+        let kind = DealKind::Forward {
+            settle_at,
+            collateral,
+        };
+        check_deal_kind(&kind, &config, &env)?;
+        let deposit = coin(coin_in.amount.mul_ceil(collateral).u128(), &coin_in.denom);
+        if info.funds[0] != deposit {
+            return Err(ContractError::WrongCoin {
+                denom: deposit.denom,
+                amount: deposit.amount,
+            });
+        }
+
+        let (deal_id, msgs) = save_new_deal(
+            deps,
+            &env,
+            &config,
+            &info.sender,
+            coin_in,
+            coin_out,
+            counterparties,
+            group,
+            expiry,
+            kind,
+            None,
+            vec![],
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "create_forward")
+            .add_attribute("creator", info.sender)
+            .add_attribute("deal_id", deal_id.to_string())
+            .add_messages(msgs))
+    }
+
+    /// Allows to agree a forward deal depositing the collateral of the requested coin.
+    pub fn accept_forward(
+        mut deps: DepsMut,
+        info: MessageInfo,
+        env: Env,
+        creator: String,
+        deal_id: u64,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        check_only_one_coin(&info.funds)?;
+
+        let creator = Addr::unchecked(creator);
+        let mut deal = deals_map().load(deps.storage, (&creator, deal_id))?;
+        let DealKind::Forward {
+            settle_at,
+            collateral,
+        } = deal.kind
+        else {
+            return Err(ContractError::WrongDealKind {});
+        };
+        // A forward cannot be agreed once its settlement is reached.
+        if settle_at.is_triggered(&env.block) {
+            return Err(ContractError::SettlementReached {});
+        }
+        check_can_accept(
+            deps.as_ref(),
+            &env,
+            &config,
+            &info.sender,
+            &creator,
+            deal_id,
+            &deal,
+        )?;
+
+        let deposit = coin(
+            deal.coin_out.amount.mul_ceil(collateral).u128(),
+            &deal.coin_out.denom,
+        );
+        if info.funds[0] != deposit {
+            return Err(ContractError::WrongCoin {
+                denom: deposit.denom,
+                amount: deposit.amount,
+            });
+        }
+
+        deal.status = DealStatus::ForwardAgreed {
+            creator_funded: creator_deposit(&deal),
+            counterparty_funded: deposit.amount,
+        };
+        deal.counterparty = Some(info.sender.clone());
+        deals_map().save(deps.storage, (&creator, deal_id), &deal)?;
+
+        let mut msgs =
+            create_mint_position_msgs(&config, deal_id, Side::Counterparty, &info.sender)?;
+        msgs.extend(cancel_oco_group(deps.branch(), &config, deal_id, &deal)?);
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_forward")
+            .add_attribute("deal_id", deal_id.to_string())
+            .add_attribute("deal_counterparty", info.sender)
+            .add_messages(msgs))
+    }
+
+    /// Allows the holders of the positions of an agreed forward to deposit the rest of their coin
+    /// before the settlement height or time.
+    pub fn fund_forward(
+        deps: DepsMut,
+        info: MessageInfo,
+        env: Env,
+        creator: String,
+        deal_id: u64,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        check_only_one_coin(&info.funds)?;
+
+        let creator = Addr::unchecked(creator);
+        let mut deal = deals_map().load(deps.storage, (&creator, deal_id))?;
+        let DealKind::Forward { settle_at, .. } = deal.kind else {
+            return Err(ContractError::WrongDealKind {});
+        };
+        let DealStatus::ForwardAgreed {
+            mut creator_funded,
+            mut counterparty_funded,
+        } = deal.status
+        else {
+            return Err(ContractError::DealNotAvailable {});
+        };
+        if settle_at.is_triggered(&env.block) {
+            return Err(ContractError::SettlementReached {});
+        }
+
+        let (side, target, funded) = if Some(&info.sender)
+            == position_holder(
+                deps.as_ref(),
+                &config,
+                &creator,
+                deal_id,
+                &deal,
+                &Side::Creator,
+            )?
+            .as_ref()
+        {
+            (Side::Creator, &deal.coin_in, &mut creator_funded)
+        } else if Some(&info.sender)
+            == position_holder(
+                deps.as_ref(),
+                &config,
+                &creator,
+                deal_id,
+                &deal,
+                &Side::Counterparty,
+            )?
+            .as_ref()
+        {
+            (Side::Counterparty, &deal.coin_out, &mut counterparty_funded)
+        } else {
+            return Err(ContractError::Unauthorized {});
+        };
+
+        let remaining = target.amount - *funded;
+        if info.funds[0].denom != target.denom || info.funds[0].amount > remaining {
+            return Err(ContractError::WrongCoin {
+                denom: target.denom.clone(),
+                amount: remaining,
+            });
+        }
+        *funded += info.funds[0].amount;
+
+        deal.status = DealStatus::ForwardAgreed {
+            creator_funded,
+            counterparty_funded,
+        };
+        deals_map().save(deps.storage, (&creator, deal_id), &deal)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "fund_forward")
+            .add_attribute("deal_id", deal_id.to_string())
+            .add_attribute("side", side.to_string())
+            .add_attribute("funded", info.funds[0].to_string()))
+    }
+
+    /// Allows anyone to settle an agreed forward after the settlement height or time. If both the
+    /// coins are fully funded they are exchanged less the market fee, otherwise a party that did
+    /// not fully fund its coin defaults and its deposit is sent to the other party without fee.
+    pub fn settle_forward(
+        mut deps: DepsMut,
+        info: MessageInfo,
+        env: Env,
+        creator: String,
+        deal_id: u64,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        let creator = Addr::unchecked(creator);
+        let deal = deals_map().load(deps.storage, (&creator, deal_id))?;
+        let DealKind::Forward { settle_at, .. } = deal.kind else {
+            return Err(ContractError::WrongDealKind {});
+        };
+        let DealStatus::ForwardAgreed {
+            creator_funded,
+            counterparty_funded,
+        } = deal.status
+        else {
+            return Err(ContractError::DealNotMatched {});
+        };
+        if !settle_at.is_triggered(&env.block) {
+            return Err(ContractError::SettlementNotReached {});
+        }
+
+        let creator_holder = position_holder(
+            deps.as_ref(),
+            &config,
+            &creator,
+            deal_id,
+            &deal,
+            &Side::Creator,
+        )?
+        .unwrap();
+        let counterparty_holder = position_holder(
+            deps.as_ref(),
+            &config,
+            &creator,
+            deal_id,
+            &deal,
+            &Side::Counterparty,
+        )?
+        .unwrap();
+
+        let creator_deposit = coin(creator_funded.u128(), &deal.coin_in.denom);
+        let counterparty_deposit = coin(counterparty_funded.u128(), &deal.coin_out.denom);
+        let fee_recipient = cw_ownable::get_ownership(deps.storage)?.owner;
+        let payouts = match (
+            creator_funded == deal.coin_in.amount,
+            counterparty_funded == deal.coin_out.amount,
+        ) {
+            (true, true) => vec![
+                (creator_holder, counterparty_deposit, config.fee),
+                (counterparty_holder, creator_deposit, config.fee),
+            ],
+            (true, false) => vec![
+                (creator_holder.clone(), creator_deposit, Decimal::zero()),
+                (creator_holder, counterparty_deposit, Decimal::zero()),
+            ],
+            (false, true) => vec![
+                (
+                    counterparty_holder.clone(),
+                    creator_deposit,
+                    Decimal::zero(),
+                ),
+                (counterparty_holder, counterparty_deposit, Decimal::zero()),
+            ],
+            (false, false) => vec![
+                (creator_holder, creator_deposit, Decimal::zero()),
+                (counterparty_holder, counterparty_deposit, Decimal::zero()),
+            ],
+        };
+        // Denied holders do not block the settlement, their coins are credited to their balance.
+        let mut msgs = vec![];
+        for (receiver, withdraw_coin, fee) in payouts {
+            msgs.extend(pay_or_credit(
+                deps.branch(),
+                &config,
+                receiver,
+                withdraw_coin,
+                fee,
+                &fee_recipient,
+            )?);
+        }
+        msgs.extend(create_burn_position_msgs(&config, deal_id, Side::Creator)?);
+        msgs.extend(create_burn_position_msgs(
+            &config,
+            deal_id,
+            Side::Counterparty,
+        )?);
+
+        deals_map().remove(deps.storage, (&creator, deal_id))?;
+
+        Ok(Response::new()
+            .add_attribute("action", "settle_forward")
+            .add_attribute("deal_id", deal_id.to_string())
+            .add_attribute("settler", info.sender)
+            .add_messages(msgs))
+    }
+
+    /// Allows to fill the available tranche of a recurring deal. Before filling, the tranches of
+    /// the elapsed intervals are rolled over or refunded to the creator.
+    pub fn accept_tranche(
+        mut deps: DepsMut,
+        info: MessageInfo,
+        env: Env,
+        creator: String,
+        deal_id: u64,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        check_only_one_coin(&info.funds)?;
+
+        let creator = Addr::unchecked(creator);
+        let mut deal = deals_map().load(deps.storage, (&creator, deal_id))?;
+        let DealKind::Recurring { tranche, .. } = deal.kind else {
+            return Err(ContractError::WrongDealKind {});
+        };
+        let refund = roll_tranches(&mut deal, &env)?;
+        let creator_holder = check_can_accept(
+            deps.as_ref(),
+            &env,
+            &config,
+            &info.sender,
+            &creator,
+            deal_id,
+            &deal,
+        )?;
+
+        let DealStatus::Recurring {
+            tranche_end,
+            available,
+        } = deal.status
+        else {
+            return Err(ContractError::DealNotAvailable {});
+        };
+        if available.is_zero() {
+            return Err(ContractError::DealNotAvailable {});
+        }
+
+        let price = coin(
+            deal.coin_out
+                .amount
+                .multiply_ratio(available, tranche)
+                .u128(),
+            &deal.coin_out.denom,
+        );
+        if info.funds[0] != price {
+            return Err(ContractError::WrongCoin {
+                denom: price.denom,
+                amount: price.amount,
+            });
+        }
+
+        deal.coin_in.amount -= available;
+        deal.status = DealStatus::Recurring {
+            tranche_end,
+            available: Uint128::zero(),
+        };
+
+        let fee_recipient = cw_ownable::get_ownership(deps.storage)?.owner;
+        let mut msgs = vec![];
+        if !refund.is_zero() {
+            msgs.extend(create_withdraw_msg_not_matched(
+                creator_holder.clone(),
+                coin(refund.u128(), &deal.coin_in.denom),
+            ));
+        }
+        msgs.extend(create_withdraw_msg_matched(
+            creator_holder,
+            price,
+            config.fee,
+            &fee_recipient,
+        ));
+        msgs.extend(create_withdraw_msg_matched(
+            info.sender.clone(),
+            coin(available.u128(), &deal.coin_in.denom),
+            config.fee,
+            &fee_recipient,
+        ));
+
+        msgs.extend(cancel_oco_group(deps.branch(), &config, deal_id, &deal)?);
+
+        // The recurring deal is completed when the whole deposit has been sold or refunded.
+        if deal.coin_in.amount.is_zero() {
+            msgs.extend(create_burn_position_msgs(&config, deal_id, Side::Creator)?);
+            deals_map().remove(deps.storage, (&creator, deal_id))?;
+        } else {
+            deals_map().save(deps.storage, (&creator, deal_id), &deal)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_tranche")
+            .add_attribute("deal_id", deal_id.to_string())
+            .add_attribute("deal_counterparty", info.sender)
+            .add_attribute("filled", available.to_string())
+            .add_messages(msgs))
+    }
+
+    /// Allows the owner to update the limits applied to new and amended deals.
+    pub fn update_limits(
+        deps: DepsMut,
+        info: MessageInfo,
+        limits: DealLimits,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        let mut config = CONFIG.load(deps.storage)?;
+        check_valid_limits(&limits, &config)?;
+        config.limits = limits;
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::new().add_attribute("action", "update_limits"))
+    }
+
+    /// Allows to propose, accept, or renounce the ownership of the market. The owner receives the
+    /// fees of the market.
+    pub fn update_ownership(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        action: cw_ownable::Action,
+    ) -> Result<Response, ContractError> {
+        let ownership = cw_ownable::update_ownership(deps, &env.block, &info.sender, action)?;
+        Ok(Response::new()
+            .add_attribute("action", "update_ownership")
+            .add_attributes(ownership.into_attributes()))
+    }
+
+    /// Allows the holder of a deal position to transfer it to another address. The new holder
+    /// becomes the only address allowed to withdraw the coins associated with the position.
+    pub fn transfer_position(
+        deps: DepsMut,
+        info: MessageInfo,
+        _env: Env,
+        creator: String,
+        deal_id: u64,
+        side: Side,
+        recipient: String,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        // Tokenized positions are transferred through the positions contract.
+        if config.positions.is_some() {
+            return Err(ContractError::PositionsTokenized {});
+        }
+
+        let creator = Addr::unchecked(creator);
+        let recipient = deps.api.addr_validate(&recipient)?;
+        check_not_denied(deps.as_ref(), &config, &info.sender)?;
+        check_not_denied(deps.as_ref(), &config, &recipient)?;
+
+        let mut deal = deals_map().load(deps.storage, (&creator, deal_id))?;
+
+        let holder = position_holder(deps.as_ref(), &config, &creator, deal_id, &deal, &side)?;
+        if Some(&info.sender) != holder.as_ref() {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // Only positions with coins still to be withdrawn can be transferred.
+        match (&side, &deal.status) {
+            (Side::Creator, DealStatus::NotMatched)
+            | (Side::Creator, DealStatus::Recurring { .. })
+            | (Side::Creator, DealStatus::Matched(WithdrawStatus::NoWithdraw))
+            | (Side::Creator, DealStatus::Matched(WithdrawStatus::CounterpartyWithdrawed))
+            | (Side::Creator, DealStatus::OptionBought)
+            | (Side::Creator, DealStatus::ForwardAgreed { .. }) => {
+                deal.creator_holder = Some(recipient.clone());
+            }
+            (Side::Counterparty, DealStatus::Matched(WithdrawStatus::NoWithdraw))
+            | (Side::Counterparty, DealStatus::Matched(WithdrawStatus::CreatorWithdrawed))
+            | (Side::Counterparty, DealStatus::OptionBought)
+            | (Side::Counterparty, DealStatus::ForwardAgreed { .. }) => {
+                deal.counterparty = Some(recipient.clone());
+            }
+            _ => return Err(ContractError::PositionWithdrawn {}),
+        }
+
+        deals_map().save(deps.storage, (&creator, deal_id), &deal)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "transfer_position")
+            .add_event(
+                Event::new("transfer_position")
+                    .add_attribute("creator", creator)
+                    .add_attribute("deal_id", deal_id.to_string())
+                    .add_attribute("side", side.to_string())
+                    .add_attribute("from", info.sender)
+                    .add_attribute("to", recipient),
+            ))
+    }
+
+    /// Create multiple deals at once. Sent funds must be equal to the sum of the coins deposited
+    /// in the deals.
+    pub fn batch_create_deals(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        deals: Vec<DealParams>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        check_group_member(deps.as_ref(), &config.group, &info.sender)?;
+        check_not_denied(deps.as_ref(), &config, &info.sender)?;
+        check_not_empty_batch(deals.len())?;
+        check_funds_match(
+            &info.funds,
+            deals.iter().map(|params| params.coin_in.clone()),
+        )?;
+
+        let mut results = vec![];
+        let mut msgs = vec![];
+        for params in deals {
+            let kind = params.kind.unwrap_or_default();
+            if matches!(kind, DealKind::Forward { .. }) {
+                return Err(ContractError::WrongDealKind {});
+            }
+            let (deal_id, deal_msgs) = save_new_deal(
+                deps.branch(),
+                &env,
+                &config,
+                &info.sender,
+                params.coin_in,
+                params.coin_out,
+                params.counterparties,
+                params.group,
+                params.expiry,
+                kind,
+                params.trigger,
+                params.coin_out_alternatives,
+            )?;
+            msgs.extend(deal_msgs);
+            results.push(DealResult {
+                creator: info.sender.clone(),
+                deal_id,
+                status: DealStatus::NotMatched,
+            });
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "batch_create_deals")
+            .add_attribute("creator", info.sender)
+            .add_attribute("deals", results.len().to_string())
+            .add_messages(msgs)
+            .set_data(to_json_binary(&BatchResponse { results })?))
+    }
+
+    /// Accept multiple deals at once. Sent funds must be equal to the sum of the coins requested
+    /// by the deals.
+    pub fn batch_accept_deals(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        deals: Vec<DealKey>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        check_not_empty_batch(deals.len())?;
+
+        let deals = deals
+            .into_iter()
+            .map(|key| {
+                let creator = Addr::unchecked(key.creator);
+                let deal = deals_map().load(deps.storage, (&creator, key.deal_id))?;
+                Ok((creator, key.deal_id, deal.coin_out))
+            })
+            .collect::<StdResult<Vec<(Addr, u64, Coin)>>>()?;
+        check_funds_match(
+            &info.funds,
+            deals.iter().map(|(_, _, coin_out)| coin_out.clone()),
+        )?;
+
+        let mut results = vec![];
+        let mut msgs = vec![];
+        for (creator, deal_id, coin_out) in deals {
+            let (deal, deal_msgs) = match_deal(
+                deps.branch(),
+                &env,
+                &config,
+                &info.sender,
+                &creator,
+                deal_id,
+                &coin_out,
+            )?;
+            msgs.extend(deal_msgs);
+            results.push(DealResult {
+                creator,
+                deal_id,
+                status: deal.status,
+            });
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "batch_accept_deals")
+            .add_attribute("deal_counterparty", info.sender)
+            .add_attribute("deals", results.len().to_string())
+            .add_messages(msgs)
+            .set_data(to_json_binary(&BatchResponse { results })?))
+    }
+
+    /// Withdraw tokens associated with multiple deals at once.
+    pub fn batch_withdraw(
+        mut deps: DepsMut,
+        info: MessageInfo,
+        env: Env,
+        deals: Vec<DealKey>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        check_not_empty_batch(deals.len())?;
+
+        let mut results = vec![];
+        let mut msgs = vec![];
+        for key in deals {
+            let creator = Addr::unchecked(key.creator);
+            let (deal, deal_msgs) = withdraw_deal(
+                deps.branch(),
+                &env,
+                &config,
+                &info.sender,
+                &creator,
+                key.deal_id,
+            )?;
+            msgs.extend(deal_msgs);
+            results.push(DealResult {
+                creator,
+                deal_id: key.deal_id,
+                status: deal.status,
+            });
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "batch_withdraw")
+            .add_attribute("deals", results.len().to_string())
+            .add_messages(msgs)
+            .set_data(to_json_binary(&BatchResponse { results })?))
+    }
+
+    /// Validate and store a new deal, returning its identifier and the messages required to
+    /// mint the creator position.
+    #[allow(clippy::too_many_arguments)]
+    pub fn save_new_deal(
+        deps: DepsMut,
+        env: &Env,
+        config: &Config,
+        creator: &Addr,
+        coin_in: Coin,
+        coin_out: Coin,
+        counterparties: Vec<String>,
+        group: Option<String>,
+        expiry: Expiration,
+        kind: DealKind,
+        trigger: Option<PriceTrigger>,
+        coin_out_alternatives: Vec<Coin>,
+    ) -> Result<(u64, Vec<CosmosMsg>), ContractError> {
+        check_allowed_coin(&coin_in.denom, config)?;
+        check_allowed_coin(&coin_out.denom, config)?;
+        check_expiry(&expiry, env)?;
+        check_duration_limits(&config.limits, &expiry, env)?;
+        check_amount_limits(&config.limits, &coin_in)?;
+        check_amount_limits(&config.limits, &coin_out)?;
+        check_deal_kind(&kind, config, env)?;
+        if trigger.is_some() && config.oracle.is_none() {
+            return Err(ContractError::MissingOracle {});
+        }
+        if !coin_out_alternatives.is_empty() && kind != DealKind::Spot {
+            return Err(ContractError::WrongDealKind {});
+        }
+        for alternative in &coin_out_alternatives {
+            check_allowed_coin(&alternative.denom, config)?;
+            if alternative.amount.is_zero() {
+                return Err(ContractError::ZeroAmount {});
+            }
+            check_amount_limits(&config.limits, alternative)?;
+        }
+
+        let counterparties = validate_counterparties(deps.as_ref(), counterparties)?;
+        let group = group
+            .map(|group| deps.api.addr_validate(&group))
+            .transpose()?;
+        if group.is_some() && !counterparties.is_empty() {
+            return Err(ContractError::CounterpartiesAndGroup {});
+        }
+        for counterparty in &counterparties {
+            check_not_denied(deps.as_ref(), config, counterparty)?;
+        }
+
+        let status = initial_status(&kind, &coin_in, env);
+        let deal = Deal {
+            coin_in,
+            coin_out,
+            coin_out_alternatives,
+            counterparties,
+            group,
+            counterparty: None,
+            expiry,
+            status,
+            creator_holder: None,
+            version: 0,
+            kind,
+            trigger,
+            oco: None,
+        };
+
+        let deal_id = next_id(deps.storage)?;
+        deals_map().save(deps.storage, (creator, deal_id), &deal)?;
+
+        let msgs = create_mint_position_msgs(config, deal_id, Side::Creator, creator)?;
+
+        Ok((deal_id, msgs))
+    }
+
+    /// Match a deal with the sender as counterparty, returning the updated deal and the messages
+    /// required to mint the counterparty position.
+    pub fn match_deal(
+        mut deps: DepsMut,
+        env: &Env,
+        config: &Config,
+        sender: &Addr,
+        creator: &Addr,
+        deal_id: u64,
+        sent_coin: &Coin,
+    ) -> Result<(Deal, Vec<CosmosMsg>), ContractError> {
+        let mut deal = deals_map().load(deps.storage, (creator, deal_id))?;
+        if deal.kind != DealKind::Spot {
+            return Err(ContractError::WrongDealKind {});
+        }
+        check_can_accept(deps.as_ref(), env, config, sender, creator, deal_id, &deal)?;
+
+        // Check if sent coins are the same of the selected deal or of one of its alternatives.
+        if &deal.coin_out != sent_coin && !deal.coin_out_alternatives.contains(sent_coin) {
+            return Err(ContractError::WrongCoin {
+                denom: deal.coin_out.denom.clone(),
+                amount: deal.coin_out.amount,
+            });
+        }
+        // The coin sent becomes the one received by the creator.
+        deal.coin_out = sent_coin.clone();
+        deal.coin_out_alternatives = vec![];
+
+        // We set the counterparty as sender and deal matched.
+        // When counterparty is set and the deal matched, counterparty address
+        // and the creator are allowed to withdraw.
+        deal.counterparty = Some(sender.clone());
+        deal.status = DealStatus::matched_no_withdraw();
+
+        deals_map().save(deps.storage, (creator, deal_id), &deal)?;
+
+        let mut msgs = create_mint_position_msgs(config, deal_id, Side::Counterparty, sender)?;
+        msgs.extend(cancel_oco_group(deps.branch(), config, deal_id, &deal)?);
+
+        Ok((deal, msgs))
+    }
+
+    /// Check that the deal terms are the ones expected by the counterparty, if specified.
+    pub fn check_deal_terms(
+        deal: &Deal,
+        config: &Config,
+        fee_recipient: &Option<Addr>,
+        expected_coin_in: Option<Coin>,
+        min_receive: Option<Uint128>,
+        expected_version: Option<u64>,
+    ) -> Result<(), ContractError> {
+        if expected_coin_in.is_some_and(|coin| coin != deal.coin_in)
+            || min_receive.is_some_and(|amount| {
+                amount_after_fee(deal.coin_in.amount, config.fee, fee_recipient) < amount
+            })
+            || expected_version.is_some_and(|version| version != deal.version)
+        {
+            return Err(ContractError::DealTermsChanged {});
+        }
+        Ok(())
+    }
+
+    /// Check that the sender can accept the deal, returning the holder of the creator position.
+    pub fn check_can_accept(
+        deps: Deps,
+        env: &Env,
+        config: &Config,
+        sender: &Addr,
+        creator: &Addr,
+        deal_id: u64,
+        deal: &Deal,
+    ) -> Result<Addr, ContractError> {
+        let creator_holder =
+            position_holder(deps, config, creator, deal_id, deal, &Side::Creator)?.unwrap();
+        if sender == creator_holder {
+            return Err(ContractError::SenderIsCreator {});
+        }
+
+        // Return error if the deal is expired or already matched.
+        if !matches!(
+            deal.status,
+            DealStatus::NotMatched | DealStatus::Recurring { .. }
+        ) || deal.expiry.is_expired(&env.block)
+        {
+            return Err(ContractError::DealNotAvailable {});
+        }
+
+        // Check if the deal is reserved and sender is not the lucky one.
+        if !deal.counterparties.is_empty() && !deal.counterparties.contains(sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+        check_group_member(deps, &config.group, sender)?;
+        check_group_member(deps, &deal.group, sender)?;
+        check_not_denied(deps, config, sender)?;
+        check_price_trigger(deps, config, deal)?;
+
+        Ok(creator_holder)
+    }
+
+    /// Update the withdraw status of a deal for the sender, returning the updated deal and the
+    /// messages required to transfer the coins.
+    pub fn withdraw_deal(
+        deps: DepsMut,
+        env: &Env,
+        config: &Config,
+        sender: &Addr,
+        creator: &Addr,
+        deal_id: u64,
+    ) -> Result<(Deal, Vec<CosmosMsg>), ContractError> {
+        let mut deal = deals_map().load(deps.storage, (creator, deal_id))?;
+
+        let is_creator = Some(sender)
+            == position_holder(
+                deps.as_ref(),
+                config,
+                creator,
+                deal_id,
+                &deal,
+                &Side::Creator,
+            )?
+            .as_ref();
+        let is_counterparty = Some(sender)
+            == position_holder(
+                deps.as_ref(),
+                config,
+                creator,
+                deal_id,
+                &deal,
+                &Side::Counterparty,
+            )?
+            .as_ref();
+
+        if !is_counterparty && !is_creator {
+            return Err(ContractError::Unauthorized);
+        }
+
+        // Denied addresses can only be refunded of their deposit in unmatched deals or expired
+        // options, if allowed by the factory.
+        let denylist = query_denylist(deps.as_ref(), config, sender)?;
+        let refund = match deal.status {
+            DealStatus::NotMatched | DealStatus::Recurring { .. } => true,
+            DealStatus::OptionBought => deal.expiry.is_expired(&env.block),
+            _ => false,
+        };
+        if denylist.denied && !(denylist.can_withdraw && refund) {
+            return Err(ContractError::Denied {
+                addr: sender.to_string(),
+            });
+        }
+
+        let fee_recipient = cw_ownable::get_ownership(deps.storage)?.owner;
+
+        // Separate the withdraw in two cases for readability
+        let mut withdrawn_sides = vec![if is_creator {
+            Side::Creator
+        } else {
+            Side::Counterparty
+        }];
+
+        // First consider the case of unmatched deal
+        let mut msgs: Vec<CosmosMsg> = match deal.status {
+            // Withdrawing a recurring deal stops the schedule and refunds the unsold coin.
+            DealStatus::NotMatched | DealStatus::Recurring { .. } if is_creator => {
+                deal.status = DealStatus::Matched(WithdrawStatus::Completed);
+                create_withdraw_msg_not_matched(
+                    sender.clone(),
+                    coin(creator_deposit(&deal).u128(), &deal.coin_in.denom),
+                )
+            }
+            // An option not exercised before expiry gives back the underlying to the creator.
+            DealStatus::OptionBought if is_creator && deal.expiry.is_expired(&env.block) => {
+                deal.status = DealStatus::Matched(WithdrawStatus::Completed);
+                let mut msgs =
+                    create_withdraw_msg_not_matched(sender.clone(), deal.coin_in.clone());
+                msgs.extend(create_burn_position_msgs(
+                    config,
+                    deal_id,
+                    Side::Counterparty,
+                )?);
+                msgs
+            }
+            // A holder of both the positions withdraws both the legs at once.
+            DealStatus::Matched(WithdrawStatus::NoWithdraw) if is_creator && is_counterparty => {
+                deal.status = DealStatus::Matched(WithdrawStatus::Completed);
+                withdrawn_sides.push(Side::Counterparty);
+                [
+                    create_withdraw_msg_matched(
+                        sender.clone(),
+                        deal.coin_out.clone(),
+                        config.fee,
+                        &fee_recipient,
+                    ),
+                    create_withdraw_msg_matched(
+                        sender.clone(),
+                        deal.coin_in.clone(),
+                        config.fee,
+                        &fee_recipient,
+                    ),
+                ]
+                .concat()
+            }
+            DealStatus::Matched(WithdrawStatus::NoWithdraw) => {
+                let withdraw_coin = if is_creator {
+                    deal.status = DealStatus::Matched(WithdrawStatus::CreatorWithdrawed);
+                    deal.coin_out.clone()
+                } else {
+                    deal.status = DealStatus::Matched(WithdrawStatus::CounterpartyWithdrawed);
+                    deal.coin_in.clone()
+                };
+                create_withdraw_msg_matched(
+                    sender.clone(),
+                    withdraw_coin,
+                    config.fee,
+                    &fee_recipient,
+                )
+            }
+            DealStatus::Matched(WithdrawStatus::CreatorWithdrawed) if is_counterparty => {
+                deal.status = DealStatus::Matched(WithdrawStatus::Completed);
+                withdrawn_sides = vec![Side::Counterparty];
+                create_withdraw_msg_matched(
+                    sender.clone(),
+                    deal.coin_in.clone(),
+                    config.fee,
+                    &fee_recipient,
+                )
+            }
+            DealStatus::Matched(WithdrawStatus::CounterpartyWithdrawed) if is_creator => {
+                deal.status = DealStatus::Matched(WithdrawStatus::Completed);
+                create_withdraw_msg_matched(
+                    sender.clone(),
+                    deal.coin_out.clone(),
+                    config.fee,
+                    &fee_recipient,
+                )
+            }
+            _ => vec![],
+        };
+
+        if msgs.is_empty() {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // The withdrawn positions have no more value.
+        for side in withdrawn_sides {
+            msgs.extend(create_burn_position_msgs(config, deal_id, side)?);
+        }
+
+        if deal.status == DealStatus::matched_and_completed() {
+            deals_map().remove(deps.storage, (creator, deal_id))?;
+        } else {
+            deals_map().save(deps.storage, (creator, deal_id), &deal)?;
+        }
+
+        Ok((deal, msgs))
+    }
+
+    /// Allows a maker to deposit allowed coins that will be used to settle their signed offers.
+    pub fn deposit(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        check_not_denied(deps.as_ref(), &config, &info.sender)?;
+        if info.funds.is_empty() {
+            return Err(ContractError::FundsError {});
+        }
+
+        for coin in info.funds.iter() {
+            check_allowed_coin(&coin.denom, &config)?;
+            BALANCES.update(
+                deps.storage,
+                (&info.sender, &coin.denom),
+                |balance| -> StdResult<_> { Ok(balance.unwrap_or_default() + coin.amount) },
+            )?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "deposit")
+            .add_attribute("maker", info.sender))
+    }
+
+    /// Allows a maker to withdraw coins from their deposited balance.
+    pub fn withdraw_balance(
+        deps: DepsMut,
+        info: MessageInfo,
+        coin: Coin,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        // Deposited coins not used by offers are withdrawn as unmatched deals.
+        let denylist = query_denylist(deps.as_ref(), &config, &info.sender)?;
+        if denylist.denied && !denylist.can_withdraw {
+            return Err(ContractError::Denied {
+                addr: info.sender.to_string(),
+            });
+        }
+        debit_balance(deps.storage, &info.sender, &coin)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "withdraw_balance")
+            .add_attribute("maker", info.sender.clone())
+            .add_messages(create_withdraw_msg_not_matched(info.sender, coin)))
+    }
+
+    /// Register or remove the public key used to verify the offers signed by the sender.
+    pub fn set_offer_signer(
+        deps: DepsMut,
+        info: MessageInfo,
+        pubkey: Option<Binary>,
+    ) -> Result<Response, ContractError> {
+        match pubkey {
+            Some(pubkey) => OFFER_SIGNERS.save(deps.storage, &info.sender, &pubkey)?,
+            None => OFFER_SIGNERS.remove(deps.storage, &info.sender),
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_offer_signer")
+            .add_attribute("maker", info.sender))
+    }
+
+    // To allow an address to accept an offer, we have to check the following conditions:
+    // 1. offer has been signed for this market by the key registered by the maker.
+    // 2. offer is not expired and its nonce has not been used.
+    // 3. sent funds are the same requested by the maker.
+    // 4. if the offer is associated with an address, sender must be that address.
+    // 5. maker has enough balance to cover the offer.
+    pub fn accept_offer(
+        deps: DepsMut,
+        info: MessageInfo,
+        env: Env,
+        offer: Offer,
+        signature: Binary,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        check_only_one_coin(&info.funds)?;
+
+        if offer.market != env.contract.address {
+            return Err(ContractError::WrongMarket {});
+        }
+        if offer.chain_id != env.block.chain_id {
+            return Err(ContractError::WrongChain {});
+        }
+
+        let maker = deps.api.addr_validate(&offer.maker)?;
+        if info.sender == maker {
+            return Err(ContractError::SenderIsCreator {});
+        }
+        check_group_member(deps.as_ref(), &config.group, &maker)?;
+        check_group_member(deps.as_ref(), &config.group, &info.sender)?;
+        check_not_denied(deps.as_ref(), &config, &maker)?;
+        check_not_denied(deps.as_ref(), &config, &info.sender)?;
+
+        let pubkey = OFFER_SIGNERS.may_load(deps.storage, &maker)?.ok_or(
+            ContractError::SignerNotRegistered {
+                maker: maker.to_string(),
+            },
+        )?;
+        let hash = Sha256::digest(offer_sign_bytes(&offer)?);
+        if !deps
+            .api
+            .secp256k1_verify(&hash, &signature, &pubkey)
+            .unwrap_or(false)
+        {
+            return Err(ContractError::InvalidSignature {});
+        }
+
+        if offer.expiry.is_expired(&env.block) {
+            return Err(ContractError::OfferExpired {});
+        }
+
+        if USED_NONCES.has(deps.storage, (&maker, offer.nonce)) {
+            return Err(ContractError::NonceAlreadyUsed { nonce: offer.nonce });
+        }
+
+        check_allowed_coin(&offer.coin_in.denom, &config)?;
+        check_allowed_coin(&offer.coin_out.denom, &config)?;
+
+        if offer.coin_out != info.funds[0] {
+            return Err(ContractError::WrongCoin {
+                denom: offer.coin_out.denom,
+                amount: offer.coin_out.amount,
+            });
+        }
+
+        if offer.counterparty.is_some() && Some(info.sender.to_string()) != offer.counterparty {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        debit_balance(deps.storage, &maker, &offer.coin_in)?;
+        USED_NONCES.save(deps.storage, (&maker, offer.nonce), &Empty {})?;
+
+        // Both parties receive the counterparty coin less the market fee.
+        let fee_recipient = cw_ownable::get_ownership(deps.storage)?.owner;
+        let mut msgs = create_withdraw_msg_matched(
+            info.sender.clone(),
+            offer.coin_in,
+            config.fee,
+            &fee_recipient,
+        );
+        msgs.extend(create_withdraw_msg_matched(
+            maker.clone(),
+            offer.coin_out,
+            config.fee,
+            &fee_recipient,
+        ));
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_offer")
+            .add_attribute("maker", maker)
+            .add_attribute("nonce", offer.nonce.to_string())
+            .add_attribute("offer_counterparty", info.sender)
+            .add_messages(msgs))
+    }
+
+    /// Allows a maker to invalidate the nonces of offers that should not be accepted anymore.
+    pub fn cancel_offers(
+        deps: DepsMut,
+        info: MessageInfo,
+        nonces: Vec<u64>,
+    ) -> Result<Response, ContractError> {
+        for nonce in nonces.iter() {
+            USED_NONCES.save(deps.storage, (&info.sender, *nonce), &Empty {})?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "cancel_offers")
+            .add_attribute("maker", info.sender)
+            .add_attribute("cancelled_offers", nonces.len().to_string()))
+    }
+
+    /// Remove a coin from the balance deposited by a maker.
+    pub fn debit_balance(
+        storage: &mut dyn Storage,
+        maker: &Addr,
+        coin: &Coin,
+    ) -> Result<(), ContractError> {
+        let balance = BALANCES
+            .may_load(storage, (maker, &coin.denom))?
+            .unwrap_or_default();
+        if balance < coin.amount {
+            return Err(ContractError::InsufficientBalance {
+                denom: coin.denom.clone(),
+                amount: balance,
+            });
+        }
+        BALANCES.save(storage, (maker, &coin.denom), &(balance - coin.amount))?;
+        Ok(())
+    }
+
+    /// Complete a matched deal, returning the messages required to transfer the coins not yet
+    /// withdrawn to both the parties. The coins of a denied party are credited to their balance.
+    pub fn settle_deal(
+        mut deps: DepsMut,
+        config: &Config,
+        creator: &Addr,
+        deal_id: u64,
+    ) -> Result<Vec<CosmosMsg>, ContractError> {
+        let deal = deals_map().load(deps.storage, (creator, deal_id))?;
+
+        let (creator_withdrawed, counterparty_withdrawed) = match deal.status {
+            DealStatus::Matched(WithdrawStatus::NoWithdraw) => (false, false),
+            DealStatus::Matched(WithdrawStatus::CreatorWithdrawed) => (true, false),
+            DealStatus::Matched(WithdrawStatus::CounterpartyWithdrawed) => (false, true),
+            _ => return Err(ContractError::DealNotMatched {}),
+        };
+
+        let fee_recipient = cw_ownable::get_ownership(deps.storage)?.owner;
+
+        // A matched deal always has both the positions.
+        let mut msgs = vec![];
+        if !creator_withdrawed {
+            let holder = position_holder(
+                deps.as_ref(),
+                config,
+                creator,
+                deal_id,
+                &deal,
+                &Side::Creator,
+            )?
+            .unwrap();
+            msgs.extend(pay_or_credit(
+                deps.branch(),
+                config,
+                holder,
+                deal.coin_out.clone(),
+                config.fee,
+                &fee_recipient,
+            )?);
+            msgs.extend(create_burn_position_msgs(config, deal_id, Side::Creator)?);
+        }
+        if !counterparty_withdrawed {
+            let holder = position_holder(
+                deps.as_ref(),
+                config,
+                creator,
+                deal_id,
+                &deal,
+                &Side::Counterparty,
+            )?
+            .unwrap();
+            msgs.extend(pay_or_credit(
+                deps.branch(),
+                config,
+                holder,
+                deal.coin_in.clone(),
+                config.fee,
+                &fee_recipient,
+            )?);
+            msgs.extend(create_burn_position_msgs(
+                config,
+                deal_id,
+                Side::Counterparty,
+            )?);
+        }
+
+        deals_map().remove(deps.storage, (creator, deal_id))?;
+
+        Ok(msgs)
+    }
+
+    /// Fill the open deals offering denom_out, cheapest first, with the coin of the taker as long as
+    /// their price, the amount of coin requested for one denom_out, does not exceed max_price. Filled
+    /// deals are settled immediately paying their creators, while a partially filled deal keeps
+    /// the remaining amounts. Returns the spent amount, the amount received by the taker before
+    /// fee, and the messages paying the creators.
+    pub fn fill_from_book(
+        mut deps: DepsMut,
+        env: &Env,
+        config: &Config,
+        taker: &Addr,
+        coin_in: &Coin,
+        denom_out: &str,
+        max_price: Decimal,
+    ) -> Result<(Uint128, Uint128, Vec<CosmosMsg>), ContractError> {
+        let fee_recipient = cw_ownable::get_ownership(deps.storage)?.owner;
+
+        let mut remaining = coin_in.amount;
+        let mut received = Uint128::zero();
+        let mut msgs = vec![];
+        // Deals cancelled by the fill of a deal of their OCO group leave the order uncovered, so
+        // the candidates are collected again until the order is filled or none is cancelled.
+        'fill: loop {
+            let candidates = book_candidates(
+                deps.as_ref(),
+                env,
+                config,
+                taker,
+                &coin(remaining.u128(), &coin_in.denom),
+                denom_out,
+                max_price,
+            )?;
+
+            let mut cancelled = false;
+            for ((creator, deal_id), mut deal, holder) in candidates {
+                // The deal has been cancelled by the fill of a deal of its OCO group.
+                if !deals_map().has(deps.storage, (&creator, deal_id)) {
+                    cancelled = true;
+                    continue;
+                }
+                let (fill_in, fill_out) = if remaining >= deal.coin_out.amount {
+                    (deal.coin_in.amount, deal.coin_out.amount)
+                } else {
+                    (
+                        deal.coin_in
+                            .amount
+                            .multiply_ratio(remaining, deal.coin_out.amount),
+                        remaining,
+                    )
+                };
+                // The remaining amount is too small to buy anything.
+                if fill_in.is_zero() {
+                    break 'fill;
+                }
+
+                remaining -= fill_out;
+                received += fill_in;
+                msgs.extend(create_withdraw_msg_matched(
+                    holder,
+                    coin(fill_out.u128(), &coin_in.denom),
+                    config.fee,
+                    &fee_recipient,
+                ));
+
+                if fill_out == deal.coin_out.amount {
+                    deals_map().remove(deps.storage, (&creator, deal_id))?;
+                    msgs.extend(create_burn_position_msgs(config, deal_id, Side::Creator)?);
+                } else {
+                    // Alternatives keep the same ratio with the coin left in the deal.
+                    let left_in = deal.coin_in.amount - fill_in;
+                    for alternative in deal.coin_out_alternatives.iter_mut() {
+                        alternative.amount = alternative
+                            .amount
+                            .multiply_ratio(left_in, deal.coin_in.amount);
+                    }
+                    deal.coin_in.amount = left_in;
+                    deal.coin_out.amount -= fill_out;
+                    deal.version += 1;
+                    deals_map().save(deps.storage, (&creator, deal_id), &deal)?;
+                }
+                msgs.extend(cancel_oco_group(deps.branch(), config, deal_id, &deal)?);
+            }
+
+            if !cancelled || remaining.is_zero() {
+                break;
+            }
+        }
+
+        Ok((coin_in.amount - remaining, received, msgs))
+    }
+
+    /// Returns the active deals that can be filled with the coin of the taker, together with the
+    /// holders of their creator positions. Deals of the taker are skipped.
+    #[allow(clippy::type_complexity)]
+    fn book_candidates(
+        deps: Deps,
+        env: &Env,
+        config: &Config,
+        taker: &Addr,
+        coin_in: &Coin,
+        denom_out: &str,
+        max_price: Decimal,
+    ) -> Result<Vec<((Addr, u64), Deal, Addr)>, ContractError> {
+        let mut candidates = vec![];
+        let mut to_cover = coin_in.amount;
+        for item in deals_map()
+            .idx
+            .book
+            .sub_prefix(denom_out.to_string())
+            .range(deps.storage, None, None, Order::Ascending)
+        {
+            let ((creator, deal_id), deal) = item?;
+            if book_price(&deal) > max_price.atomics().u128() {
+                break;
+            }
+            // Deals offering the same coin can request different ones in markets with other coins.
+            if deal.coin_out.denom != coin_in.denom {
+                continue;
+            }
+            if deal.expiry.is_expired(&env.block) {
+                continue;
+            }
+            let holder =
+                position_holder(deps, config, &creator, deal_id, &deal, &Side::Creator)?.unwrap();
+            if holder == taker || query_denylist(deps, config, &holder)?.denied {
+                continue;
+            }
+
+            let covered = deal.coin_out.amount >= to_cover;
+            to_cover = to_cover.saturating_sub(deal.coin_out.amount);
+            candidates.push(((creator, deal_id), deal, holder));
+            if covered {
+                break;
+            }
+        }
+        Ok(candidates)
+    }
+
+    /// Returns the coin bought with the given one. It can be omitted only for the first and the
+    /// second coin of the market, which are bought one with the other.
+    pub fn counter_denom(
+        denom: &str,
+        denom_out: Option<String>,
+        config: &Config,
+    ) -> Result<String, ContractError> {
+        let denom_out = match denom_out {
+            Some(denom_out) => {
+                check_allowed_coin(&denom_out, config)?;
+                denom_out
+            }
+            None if denom == config.first_coin => config.second_coin.clone(),
+            None if denom == config.second_coin => config.first_coin.clone(),
+            None => {
+                return Err(ContractError::MissingDenomOut {
+                    denom: denom.to_string(),
+                })
+            }
+        };
+        if denom_out == denom {
+            return Err(ContractError::CoinError {
+                first_coin: denom.to_string(),
+                second_coin: denom_out,
+            });
+        }
+        Ok(denom_out)
+    }
+
+    /// Returns the address holding a position of the deal. The counterparty position exists only
+    /// after the deal has been matched. When positions are tokenized, the holder of a position not
+    /// yet withdrawn is the owner of the associated token.
+    pub fn position_holder(
+        deps: Deps,
+        config: &Config,
+        creator: &Addr,
+        deal_id: u64,
+        deal: &Deal,
+        side: &Side,
+    ) -> StdResult<Option<Addr>> {
+        let holder = match side {
+            Side::Creator => Some(
+                deal.creator_holder
+                    .clone()
+                    .unwrap_or_else(|| creator.clone()),
+            ),
+            Side::Counterparty => match deal.status {
+                DealStatus::NotMatched | DealStatus::Recurring { .. } => None,
+                DealStatus::Matched(_)
+                | DealStatus::OptionBought
+                | DealStatus::ForwardAgreed { .. } => deal.counterparty.clone(),
+            },
+        };
+
+        match &config.positions {
+            Some(positions) if is_position_open(deal, side) => {
+                let resp: OwnerOfResponse = deps.querier.query_wasm_smart(
+                    positions,
+                    &PositionsQueryMsg::OwnerOf {
+                        token_id: position_token_id(deal_id, side),
+                        include_expired: None,
+                    },
+                )?;
+                Ok(Some(deps.api.addr_validate(&resp.owner)?))
+            }
+            _ => Ok(holder),
+        }
+    }
+
+    /// Returns true if the coins associated with a position have not been withdrawn yet.
+    pub fn is_position_open(deal: &Deal, side: &Side) -> bool {
+        matches!(
+            (side, &deal.status),
+            (Side::Creator, DealStatus::NotMatched)
+                | (Side::Creator, DealStatus::Recurring { .. })
+                | (
+                    Side::Creator,
+                    DealStatus::Matched(WithdrawStatus::NoWithdraw)
+                )
+                | (
+                    Side::Creator,
+                    DealStatus::Matched(WithdrawStatus::CounterpartyWithdrawed)
+                )
+                | (
+                    Side::Counterparty,
+                    DealStatus::Matched(WithdrawStatus::NoWithdraw)
+                )
+                | (
+                    Side::Counterparty,
+                    DealStatus::Matched(WithdrawStatus::CreatorWithdrawed)
+                )
+                | (_, DealStatus::OptionBought)
+                | (_, DealStatus::ForwardAgreed { .. })
+        )
+    }
+
+    /// Returns the identifier of the token representing a deal position.
+    pub fn position_token_id(deal_id: u64, side: &Side) -> String {
+        format!("{deal_id}-{side}")
+    }
+
+    /// Create the message to mint a position token to the owner if positions are tokenized.
+    pub fn create_mint_position_msgs(
+        config: &Config,
+        deal_id: u64,
+        side: Side,
+        owner: &Addr,
+    ) -> StdResult<Vec<CosmosMsg>> {
+        let Some(positions) = &config.positions else {
+            return Ok(vec![]);
+        };
+        let msg: CosmosMsg = WasmMsg::Execute {
+            contract_addr: positions.to_string(),
+            msg: to_json_binary(&PositionsExecuteMsg::Mint {
+                token_id: position_token_id(deal_id, &side),
+                owner: owner.to_string(),
+                token_uri: None,
+                extension: None,
+            })?,
+            funds: vec![],
+        }
+        .into();
+        Ok(vec![msg])
+    }
+
+    /// Create the message to burn a position token if positions are tokenized.
+    pub fn create_burn_position_msgs(
+        config: &Config,
+        deal_id: u64,
+        side: Side,
+    ) -> StdResult<Vec<CosmosMsg>> {
+        let Some(positions) = &config.positions else {
+            return Ok(vec![]);
+        };
+        let msg: CosmosMsg = WasmMsg::Execute {
+            contract_addr: positions.to_string(),
+            msg: to_json_binary(&PositionsExecuteMsg::Extension {
+                msg: PositionsExtensionMsg::Burn {
+                    token_id: position_token_id(deal_id, &side),
+                },
+            })?,
+            funds: vec![],
+        }
+        .into();
+        Ok(vec![msg])
+    }
+
+    /// Check that only one coin has been sent to the contract.
+    pub fn check_only_one_coin(funds: &[Coin]) -> Result<(), ContractError> {
+        if funds.len() != 1 {
+            return Err(ContractError::FundsError {});
+        }
+        Ok(())
+    }
+
+    /// Check that a batch contains at least one deal.
+    pub fn check_not_empty_batch(len: usize) -> Result<(), ContractError> {
+        if len == 0 {
+            return Err(ContractError::EmptyBatch {});
         }
-        let mut deal = DEALS.load(deps.storage, (&creator, deal_id))?;
+        Ok(())
+    }
 
-        // Return error if the deal is expired or already matched.
-        if deal.status != DealStatus::NotMatched || deal.timeout < env.block.height {
-            return Err(ContractError::DealNotAvailable {});
+    /// Check that the sent funds are equal to the sum of the expected coins.
+    pub fn check_funds_match(
+        funds: &[Coin],
+        expected: impl Iterator<Item = Coin>,
+    ) -> Result<(), ContractError> {
+        let mut sent: BTreeMap<String, Uint128> = BTreeMap::new();
+        for coin in funds {
+            *sent.entry(coin.denom.clone()).or_default() += coin.amount;
+        }
+        let mut required: BTreeMap<String, Uint128> = BTreeMap::new();
+        for coin in expected {
+            *required.entry(coin.denom).or_default() += coin.amount;
         }
 
-        // Check if sent coins are the same of the selected deal.
-        if deal.coin_out != info.funds[0] {
-            return Err(ContractError::WrongCoin {
-                denom: deal.coin_out.denom.clone(),
-                amount: deal.coin_out.amount,
+        if sent != required {
+            return Err(ContractError::WrongFunds {
+                expected: required
+                    .into_iter()
+                    .map(|(denom, amount)| format!("{amount}{denom}"))
+                    .collect::<Vec<String>>()
+                    .join(","),
             });
         }
+        Ok(())
+    }
 
-        // Check if the deal is reserved and sender is not the lucky one.
-        if deal.counterparty.is_some() && Some(info.sender.clone()) != deal.counterparty {
-            return Err(ContractError::Unauthorized {});
+    /// Validate the addresses allowed to accept a deal, up to the maximum allowed.
+    pub fn validate_counterparties(
+        deps: Deps,
+        counterparties: Vec<String>,
+    ) -> Result<Vec<Addr>, ContractError> {
+        if counterparties.len() > MAX_COUNTERPARTIES {
+            return Err(ContractError::TooManyCounterparties {
+                max: MAX_COUNTERPARTIES,
+            });
         }
+        counterparties
+            .iter()
+            .map(|addr| Ok(deps.api.addr_validate(addr)?))
+            .collect()
+    }
 
-        // We set the counterparty as sender and deal matched.
-        // When counterparty is set and the deal matched, counterparty address
-        // and the creator are allowed to withdraw.
-        deal.counterparty = Some(info.sender);
-        deal.status = DealStatus::matched_no_withdraw();
+    /// Check that the expiration of a deal is not already reached.
+    pub fn check_expiry(expiry: &Expiration, env: &Env) -> Result<(), ContractError> {
+        if expiry.is_expired(&env.block) {
+            return Err(ContractError::InvalidExpiry {});
+        }
+        Ok(())
+    }
 
-        DEALS.save(deps.storage, (&creator, deal_id), &deal)?;
+    /// Check that the limits refer to the market coins and that minimum values do not exceed
+    /// maximum ones.
+    pub fn check_valid_limits(limits: &DealLimits, config: &Config) -> Result<(), ContractError> {
+        if let (Some(min), Some(max)) = (limits.min_duration, limits.max_duration) {
+            let valid = match (min, max) {
+                (Duration::Height(min), Duration::Height(max)) => min <= max,
+                (Duration::Time(min), Duration::Time(max)) => min <= max,
+                _ => false,
+            };
+            if !valid {
+                return Err(ContractError::InvalidLimits {});
+            }
+        }
+        for amount in &limits.amounts {
+            check_allowed_coin(&amount.denom, config)?;
+            if let (Some(min), Some(max)) = (amount.min, amount.max) {
+                if min > max {
+                    return Err(ContractError::InvalidLimits {});
+                }
+            }
+        }
+        Ok(())
+    }
 
-        Ok(Response::new()
-            .add_attribute("action", "accept_deal")
-            .add_attribute("deal_counterparty", deal.counterparty.unwrap()))
+    /// Check that the expiry of a deal is within the market duration limits.
+    pub fn check_duration_limits(
+        limits: &DealLimits,
+        expiry: &Expiration,
+        env: &Env,
+    ) -> Result<(), ContractError> {
+        if let Some(min) = limits.min_duration {
+            match expiry.partial_cmp(&min.after(&env.block)) {
+                None => return Err(ContractError::ExpiryUnitMismatch {}),
+                Some(Ordering::Less) => return Err(ContractError::DurationBelowMinimum {}),
+                _ => {}
+            }
+        }
+        if let Some(max) = limits.max_duration {
+            match expiry.partial_cmp(&max.after(&env.block)) {
+                None => return Err(ContractError::ExpiryUnitMismatch {}),
+                Some(Ordering::Greater) => return Err(ContractError::DurationAboveMaximum {}),
+                _ => {}
+            }
+        }
+        Ok(())
     }
 
-    /// Allows to withdraw tokens asscoiated with a deal. If no one accepted the deal, the creator can
-    /// close it and withdraw coins without deducted fee. If a deal is close, fee are deducted from
-    /// both the parties.
-    pub fn withdraw(
-        deps: DepsMut,
-        info: MessageInfo,
-        _env: Env,
-        creator: String,
-        deal_id: u64,
-    ) -> Result<Response, ContractError> {
-        let config = CONFIG.load(deps.storage)?;
+    /// Check that the amount of a coin is within the market amount limits of its denom.
+    pub fn check_amount_limits(limits: &DealLimits, coin: &Coin) -> Result<(), ContractError> {
+        let Some(amount_limits) = limits.amounts.iter().find(|l| l.denom == coin.denom) else {
+            return Ok(());
+        };
+        if let Some(min) = amount_limits.min {
+            if coin.amount < min {
+                return Err(ContractError::AmountBelowMinimum {
+                    denom: coin.denom.clone(),
+                    min,
+                });
+            }
+        }
+        if let Some(max) = amount_limits.max {
+            if coin.amount > max {
+                return Err(ContractError::AmountAboveMaximum {
+                    denom: coin.denom.clone(),
+                    max,
+                });
+            }
+        }
+        Ok(())
+    }
 
-        let creator = Addr::unchecked(creator);
+    /// Check that the parameters of the deal kind are valid for the market.
+    pub fn check_deal_kind(
+        kind: &DealKind,
+        config: &Config,
+        env: &Env,
+    ) -> Result<(), ContractError> {
+        match kind {
+            DealKind::Spot => {}
+            DealKind::Option { premium } => {
+                check_allowed_coin(&premium.denom, config)?;
+                if premium.amount.is_zero() {
+                    return Err(ContractError::ZeroAmount {});
+                }
+            }
+            DealKind::Forward {
+                settle_at,
+                collateral,
+            } => {
+                if collateral.is_zero() || *collateral > Decimal::one() {
+                    return Err(ContractError::InvalidCollateral {});
+                }
+                if settle_at.is_triggered(&env.block) {
+                    return Err(ContractError::InvalidSettlement {});
+                }
+            }
+            DealKind::Recurring {
+                interval, tranche, ..
+            } => {
+                if tranche.is_zero() {
+                    return Err(ContractError::ZeroAmount {});
+                }
+                if matches!(interval, Duration::Height(0) | Duration::Time(0)) {
+                    return Err(ContractError::InvalidInterval {});
+                }
+            }
+        }
+        Ok(())
+    }
 
-        let mut deal = DEALS.load(deps.storage, (&creator, deal_id))?;
+    /// Check that the oracle price of the deal pair satisfies the trigger of the deal, if any.
+    pub fn check_price_trigger(
+        deps: Deps,
+        config: &Config,
+        deal: &Deal,
+    ) -> Result<(), ContractError> {
+        let Some(trigger) = &deal.trigger else {
+            return Ok(());
+        };
+        let oracle = config
+            .oracle
+            .as_ref()
+            .ok_or(ContractError::MissingOracle {})?;
+        let (base, quote) = market_pair(config, &deal.coin_in.denom, &deal.coin_out.denom);
+        let resp: PriceResponse = deps
+            .querier
+            .query_wasm_smart(oracle, &OracleQueryMsg::Price { base, quote })?;
+        let reached = match trigger {
+            PriceTrigger::Above(threshold) => resp.price >= *threshold,
+            PriceTrigger::Below(threshold) => resp.price <= *threshold,
+        };
+        if !reached {
+            return Err(ContractError::TriggerNotReached {});
+        }
+        Ok(())
+    }
 
-        let is_creator = creator == info.sender;
-        let is_counterparty = Some(info.sender.clone()) == deal.counterparty;
+    /// Cancel the open deals of the OCO group of an accepted deal, if any, returning the messages
+    /// required to refund their creators.
+    pub fn cancel_oco_group(
+        deps: DepsMut,
+        config: &Config,
+        deal_id: u64,
+        deal: &Deal,
+    ) -> Result<Vec<CosmosMsg>, ContractError> {
+        let Some(oco) = deal.oco else {
+            return Ok(vec![]);
+        };
+        let siblings = deals_map()
+            .idx
+            .oco
+            .prefix(oco)
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
 
-        if !is_counterparty && !is_creator {
-            return Err(ContractError::Unauthorized);
+        let mut msgs = vec![];
+        for ((creator, sibling_id), sibling) in siblings {
+            if sibling_id == deal_id
+                || !matches!(
+                    sibling.status,
+                    DealStatus::NotMatched | DealStatus::Recurring { .. }
+                )
+            {
+                continue;
+            }
+            let holder = position_holder(
+                deps.as_ref(),
+                config,
+                &creator,
+                sibling_id,
+                &sibling,
+                &Side::Creator,
+            )?
+            .unwrap();
+            msgs.extend(create_withdraw_msg_not_matched(
+                holder,
+                coin(creator_deposit(&sibling).u128(), &sibling.coin_in.denom),
+            ));
+            msgs.extend(create_burn_position_msgs(
+                config,
+                sibling_id,
+                Side::Creator,
+            )?);
+            deals_map().remove(deps.storage, (&creator, sibling_id))?;
         }
+        Ok(msgs)
+    }
 
-        // Separate the withdraw in two cases for readability
+    /// Returns the status of a new deal of the given kind.
+    pub fn initial_status(kind: &DealKind, coin_in: &Coin, env: &Env) -> DealStatus {
+        match kind {
+            DealKind::Recurring {
+                interval, tranche, ..
+            } => DealStatus::Recurring {
+                tranche_end: interval.after(&env.block),
+                available: (*tranche).min(coin_in.amount),
+            },
+            _ => DealStatus::NotMatched,
+        }
+    }
 
-        // First consider the case of unmatched deal
-        let msgs: Vec<CosmosMsg> = match deal.status {
-            DealStatus::NotMatched if is_creator => {
-                deal.status = DealStatus::Matched(WithdrawStatus::Completed);
-                create_withdraw_msg_not_matched(info.sender, deal.coin_in.clone())
-            }
-            DealStatus::Matched(WithdrawStatus::NoWithdraw) => {
-                let withdraw_coin = if is_creator {
-                    deal.status = DealStatus::Matched(WithdrawStatus::CreatorWithdrawed);
-                    deal.coin_out.clone()
-                } else {
-                    deal.status = DealStatus::Matched(WithdrawStatus::CounterpartyWithdrawed);
-                    deal.coin_in.clone()
-                };
-                create_withdraw_msg_matched(info.sender, withdraw_coin, config)
-            }
-            DealStatus::Matched(WithdrawStatus::CreatorWithdrawed) if !is_creator => {
-                deal.status = DealStatus::Matched(WithdrawStatus::Completed);
-                create_withdraw_msg_matched(info.sender, deal.coin_in.clone(), config)
+    /// Opens the tranches of a recurring deal for the intervals elapsed since the end of the
+    /// current one, returning the amount of the offered coin to refund to the creator for the
+    /// unfilled tranches that are not rolled over.
+    pub fn roll_tranches(deal: &mut Deal, env: &Env) -> StdResult<Uint128> {
+        let (
+            DealKind::Recurring {
+                interval,
+                tranche,
+                rollover,
+            },
+            DealStatus::Recurring {
+                mut tranche_end,
+                mut available,
+            },
+        ) = (&deal.kind, &deal.status)
+        else {
+            return Ok(Uint128::zero());
+        };
+
+        // Number of tranche ends reached, computed at once to process any elapsed time.
+        let elapsed = match (tranche_end, interval) {
+            (Expiration::AtHeight(end), Duration::Height(step)) if env.block.height >= end => {
+                (env.block.height - end) / step + 1
             }
-            DealStatus::Matched(WithdrawStatus::CounterpartyWithdrawed) if is_creator => {
-                deal.status = DealStatus::Matched(WithdrawStatus::Completed);
-                create_withdraw_msg_matched(info.sender, deal.coin_out.clone(), config)
+            (Expiration::AtTime(end), Duration::Time(step)) if env.block.time >= end => {
+                (env.block.time.nanos() - end.nanos()) / (step * 1_000_000_000) + 1
             }
-            _ => vec![],
+            _ => return Ok(Uint128::zero()),
         };
 
-        if msgs.is_empty() {
-            return Err(ContractError::Unauthorized {});
+        let mut refund = Uint128::zero();
+        if *rollover {
+            available = available
+                .saturating_add(tranche.saturating_mul(Uint128::from(elapsed)))
+                .min(deal.coin_in.amount);
+        } else {
+            // The current tranche and all the ones opened and ended meanwhile are refunded.
+            refund = available
+                .saturating_add(tranche.saturating_mul(Uint128::from(elapsed - 1)))
+                .min(deal.coin_in.amount);
+            deal.coin_in.amount -= refund;
+            available = (*tranche).min(deal.coin_in.amount);
+        }
+        tranche_end = match interval {
+            Duration::Height(step) => tranche_end + Duration::Height(step * elapsed),
+            Duration::Time(step) => tranche_end + Duration::Time(step * elapsed),
+        }?;
+        // Once the whole deposit is available, following intervals change nothing.
+        if *rollover && available == deal.coin_in.amount {
+            tranche_end = interval.after(&env.block);
         }
 
-        if deal.status == DealStatus::matched_and_completed() {
-            DEALS.remove(deps.storage, (&creator, deal_id))
-        } else {
-            DEALS.save(deps.storage, (&creator, deal_id), &deal)?;
+        deal.status = DealStatus::Recurring {
+            tranche_end,
+            available,
+        };
+        Ok(refund)
+    }
+
+    /// Returns the amount of the offered coin deposited by the creator of the deal.
+    pub fn creator_deposit(deal: &Deal) -> Uint128 {
+        match (&deal.kind, &deal.status) {
+            (_, DealStatus::ForwardAgreed { creator_funded, .. }) => *creator_funded,
+            (DealKind::Forward { collateral, .. }, _) => deal.coin_in.amount.mul_ceil(*collateral),
+            _ => deal.coin_in.amount,
         }
+    }
 
-        Ok(Response::new()
-            .add_attribute("action", "withdraw")
-            .add_messages(msgs))
+    /// Check that the address is a member of the group, if any.
+    pub fn check_group_member(
+        deps: Deps,
+        group: &Option<Addr>,
+        addr: &Addr,
+    ) -> Result<(), ContractError> {
+        let Some(group) = group else {
+            return Ok(());
+        };
+        if Cw4Contract::new(group.clone())
+            .is_member(&deps.querier, addr, None)?
+            .is_none()
+        {
+            return Err(ContractError::NotGroupMember {
+                addr: addr.to_string(),
+            });
+        }
+        Ok(())
     }
 
-    /// Check that only one coin has been sent to the contract.
-    pub fn check_only_one_coin(funds: &[Coin]) -> Result<(), ContractError> {
-        if funds.len() != 1 {
-            return Err(ContractError::FundsError {});
+    /// Query the denylist of the factory, if any, for the given address.
+    pub fn query_denylist(deps: Deps, config: &Config, addr: &Addr) -> StdResult<IsDeniedResponse> {
+        let Some(factory) = &config.factory else {
+            return Ok(IsDeniedResponse {
+                denied: false,
+                can_withdraw: true,
+            });
+        };
+        deps.querier.query_wasm_smart(
+            factory,
+            &DenylistQueryMsg::IsDenied {
+                address: addr.to_string(),
+            },
+        )
+    }
+
+    /// Check that the address is not in the denylist of the factory, if any.
+    pub fn check_not_denied(deps: Deps, config: &Config, addr: &Addr) -> Result<(), ContractError> {
+        if query_denylist(deps, config, addr)?.denied {
+            return Err(ContractError::Denied {
+                addr: addr.to_string(),
+            });
         }
         Ok(())
     }
 
     /// Check that the denom is an allowed coin for the market.
     pub fn check_allowed_coin(denom: &str, config: &Config) -> Result<(), ContractError> {
-        if denom != config.first_coin && denom != config.second_coin {
+        if denom != config.first_coin
+            && denom != config.second_coin
+            && !config.other_coins.iter().any(|coin| coin == denom)
+        {
             return Err(ContractError::CoinNotAllowed {});
         }
         Ok(())
     }
 
+    /// Returns two denoms of the market ordered as its coins: the first coin, the second coin,
+    /// and then the other coins.
+    pub fn market_pair(config: &Config, denom_a: &str, denom_b: &str) -> (String, String) {
+        let rank = |denom: &str| {
+            [&config.first_coin, &config.second_coin]
+                .into_iter()
+                .chain(&config.other_coins)
+                .position(|coin| coin == denom)
+        };
+        if rank(denom_b) < rank(denom_a) {
+            (denom_b.to_string(), denom_a.to_string())
+        } else {
+            (denom_a.to_string(), denom_b.to_string())
+        }
+    }
+
     /// Create a bank transfer message to refund the entire amount.
     pub fn create_withdraw_msg_not_matched(receiver: Addr, coin: Coin) -> Vec<CosmosMsg> {
         let msg: CosmosMsg = BankMsg::Send {
@@ -274,15 +3001,71 @@ pub mod execute {
         vec![msg]
     }
 
+    /// Returns the amount left after the market fee, applied only if the market has an owner.
+    pub fn amount_after_fee(
+        amount: Uint128,
+        fee: Decimal,
+        fee_recipient: &Option<Addr>,
+    ) -> Uint128 {
+        match fee_recipient {
+            Some(_) => amount - amount * fee,
+            None => amount,
+        }
+    }
+
+    /// Create the messages to pay a coin less fee to the holder of a position. If the holder is
+    /// denied, the coin is credited to their balance, withdrawable when allowed by the factory.
+    pub fn pay_or_credit(
+        deps: DepsMut,
+        config: &Config,
+        receiver: Addr,
+        withdraw_coin: Coin,
+        fee: Decimal,
+        fee_recipient: &Option<Addr>,
+    ) -> Result<Vec<CosmosMsg>, ContractError> {
+        if !query_denylist(deps.as_ref(), config, &receiver)?.denied {
+            return Ok(create_withdraw_msg_matched(
+                receiver,
+                withdraw_coin,
+                fee,
+                fee_recipient,
+            ));
+        }
+
+        let receiver_amount = amount_after_fee(withdraw_coin.amount, fee, fee_recipient);
+        BALANCES.update(
+            deps.storage,
+            (&receiver, &withdraw_coin.denom),
+            |balance| -> StdResult<_> { Ok(balance.unwrap_or_default() + receiver_amount) },
+        )?;
+
+        let fee_amount = withdraw_coin.amount - receiver_amount;
+        match fee_recipient {
+            Some(fee_recipient) if !fee_amount.is_zero() => Ok(vec![BankMsg::Send {
+                to_address: fee_recipient.to_string(),
+                amount: vec![coin(fee_amount.u128(), withdraw_coin.denom)],
+            }
+            .into()]),
+            _ => Ok(vec![]),
+        }
+    }
+
     /// Create a bank transfer message to the receiver and a bank transfer message for th fee if any.
+    /// The fee is sent to the current owner of the market and it is not applied if the ownership
+    /// has been renounced.
     pub fn create_withdraw_msg_matched(
         receiver: Addr,
         withdraw_coin: Coin,
-        config: Config,
+        fee: Decimal,
+        fee_recipient: &Option<Addr>,
     ) -> Vec<CosmosMsg> {
         let mut msgs = vec![];
 
-        let fee_amount = withdraw_coin.amount * config.fee;
+        let Some(fee_recipient) = fee_recipient else {
+            return create_withdraw_msg_not_matched(receiver, withdraw_coin);
+        };
+
+        let fee_amount = withdraw_coin.amount * fee;
         let receiver_amount = withdraw_coin.amount - fee_amount;
         msgs.push(
             BankMsg::Send {
@@ -295,7 +3078,7 @@ pub mod execute {
         if fee_amount != Uint128::zero() {
             msgs.push(
                 BankMsg::Send {
-                    to_address: config.owner.to_string(),
+                    to_address: fee_recipient.to_string(),
                     amount: vec![coin(fee_amount.u128(), withdraw_coin.denom)],
                 }
                 .into(),
@@ -305,16 +3088,27 @@ pub mod execute {
     }
 }
 
+/// Returns the bytes that have to be hashed and signed by a maker to create a valid offer.
+pub fn offer_sign_bytes(offer: &Offer) -> StdResult<Binary> {
+    Ok(Binary::from(to_json_vec(offer)?))
+}
+
 pub mod query {
     use common::market::Deal;
-    use cosmwasm_std::{Addr, Order};
+    use cosmwasm_std::{Addr, Order, Uint128};
 
     use crate::{
-        msg::{AllDealsResponse, DealsByCreatorResponse},
-        state::DEALS,
+        msg::{
+            AllDealsResponse, BalanceResponse, DealsByCreatorResponse, NonceUsedResponse,
+            OcoGroupResponse, OfferSignerResponse, OrderBookLevel, OrderBookResponse,
+        },
+        state::{book_price, deals_map, BALANCES, OFFER_SIGNERS, USED_NONCES},
     };
 
-    use super::*;
+    use super::{
+        execute::{check_allowed_coin, counter_denom},
+        *,
+    };
 
     pub fn get_config(deps: Deps) -> StdResult<Config> {
         CONFIG.load(deps.storage)
@@ -327,12 +3121,12 @@ pub mod query {
         creator: String,
     ) -> StdResult<DealsByCreatorResponse> {
         let creator = Addr::unchecked(creator);
-        let deals = DEALS
+        let deals = deals_map()
             .prefix(&creator)
             .range(deps.storage, None, None, Order::Ascending)
             .filter_map(|item| {
                 item.ok().and_then(|(id, deal)| {
-                    if deal.timeout >= env.block.height {
+                    if !deal.expiry.is_expired(&env.block) {
                         Some(Ok((id, deal)))
                     } else {
                         None
@@ -344,13 +3138,28 @@ pub mod query {
         Ok(DealsByCreatorResponse { deals })
     }
 
+    /// Returns the deals of a one-cancels-other group.
+    pub fn get_oco_group(deps: Deps, group_id: u64) -> StdResult<OcoGroupResponse> {
+        // Deals not grouped are indexed under zero.
+        if group_id == 0 {
+            return Ok(OcoGroupResponse { deals: vec![] });
+        }
+        let deals = deals_map()
+            .idx
+            .oco
+            .prefix(group_id)
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<((Addr, u64), Deal)>>>()?;
+        Ok(OcoGroupResponse { deals })
+    }
+
     /// Returns all active deals.
     pub fn get_all_deals(deps: Deps, env: Env) -> StdResult<AllDealsResponse> {
-        let deals = DEALS
+        let deals = deals_map()
             .range(deps.storage, None, None, Order::Ascending)
             .filter_map(|item| {
                 item.ok().and_then(|(id, deal)| {
-                    if deal.timeout >= env.block.height {
+                    if !deal.expiry.is_expired(&env.block) {
                         Some(Ok((id, deal)))
                     } else {
                         None
@@ -360,6 +3169,203 @@ pub mod query {
             .collect::<StdResult<Vec<((Addr, u64), Deal)>>>()?;
         Ok(AllDealsResponse { deals })
     }
+
+    /// Returns the open deals not reserved to counterparties or a group aggregated in price levels
+    /// for both the sides of a pair of the market coins, best first.
+    pub fn get_order_book(
+        deps: Deps,
+        env: Env,
+        depth: Option<u32>,
+        first_denom: Option<String>,
+        second_denom: Option<String>,
+    ) -> StdResult<OrderBookResponse> {
+        let config = CONFIG.load(deps.storage)?;
+        let depth = depth
+            .unwrap_or(DEFAULT_ORDER_BOOK_DEPTH)
+            .min(MAX_ORDER_BOOK_DEPTH) as usize;
+
+        let first_denom = first_denom.unwrap_or_else(|| config.first_coin.clone());
+        let second_denom = counter_denom(&first_denom, second_denom, &config)
+            .and_then(|second_denom| {
+                check_allowed_coin(&first_denom, &config)?;
+                Ok(second_denom)
+            })
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+        Ok(OrderBookResponse {
+            sell_first_coin: get_book_levels(
+                deps,
+                &env,
+                &first_denom,
+                &second_denom,
+                depth,
+                false,
+            )?,
+            sell_second_coin: get_book_levels(
+                deps,
+                &env,
+                &second_denom,
+                &first_denom,
+                depth,
+                true,
+            )?,
+        })
+    }
+
+    /// Aggregates the active deals offering a denom for the counter denom in up to depth price
+    /// levels, with prices inverted for the deals offering the second coin of the pair.
+    fn get_book_levels(
+        deps: Deps,
+        env: &Env,
+        denom: &str,
+        counter_denom: &str,
+        depth: usize,
+        inverse: bool,
+    ) -> StdResult<Vec<OrderBookLevel>> {
+        let mut levels: Vec<(u128, OrderBookLevel)> = vec![];
+        for item in deals_map().idx.book.sub_prefix(denom.to_string()).range(
+            deps.storage,
+            None,
+            None,
+            Order::Ascending,
+        ) {
+            let (_, deal) = item?;
+            if deal.expiry.is_expired(&env.block) || deal.coin_out.denom != counter_denom {
+                continue;
+            }
+
+            let price = book_price(&deal);
+            let full = levels.len() == depth;
+            match levels.last_mut() {
+                Some((level_price, level)) if *level_price == price => {
+                    level.offered = level.offered.checked_add(deal.coin_in.amount)?;
+                    level.requested = level.requested.checked_add(deal.coin_out.amount)?;
+                    level.deals += 1;
+                }
+                _ if full => break,
+                _ => levels.push((
+                    price,
+                    OrderBookLevel {
+                        price: Decimal::zero(),
+                        offered: deal.coin_in.amount,
+                        requested: deal.coin_out.amount,
+                        deals: 1,
+                    },
+                )),
+            }
+        }
+
+        Ok(levels
+            .into_iter()
+            .map(|(_, mut level)| {
+                let (second_amount, first_amount) = if inverse {
+                    (level.offered, level.requested)
+                } else {
+                    (level.requested, level.offered)
+                };
+                level.price = level_price(second_amount, first_amount);
+                level
+            })
+            .collect())
+    }
+
+    /// Returns the price of a level, saturating to the maximum decimal.
+    fn level_price(second_amount: Uint128, first_amount: Uint128) -> Decimal {
+        Decimal::checked_from_ratio(second_amount, first_amount).unwrap_or(Decimal::MAX)
+    }
+
+    /// Returns the amount of a denom deposited by a maker.
+    pub fn get_balance(deps: Deps, maker: String, denom: String) -> StdResult<BalanceResponse> {
+        let maker = Addr::unchecked(maker);
+        let amount = BALANCES
+            .may_load(deps.storage, (&maker, &denom))?
+            .unwrap_or_default();
+        Ok(BalanceResponse { amount })
+    }
+
+    /// Returns the public key registered by a maker to sign offers.
+    pub fn get_offer_signer(deps: Deps, maker: String) -> StdResult<OfferSignerResponse> {
+        let maker = Addr::unchecked(maker);
+        let pubkey = OFFER_SIGNERS.may_load(deps.storage, &maker)?;
+        Ok(OfferSignerResponse { pubkey })
+    }
+
+    /// Returns if the nonce of an offer has been already accepted or cancelled.
+    pub fn get_nonce_used(deps: Deps, maker: String, nonce: u64) -> StdResult<NonceUsedResponse> {
+        let maker = Addr::unchecked(maker);
+        let used = USED_NONCES.has(deps.storage, (&maker, nonce));
+        Ok(NonceUsedResponse { used })
+    }
+}
+
+pub mod reply {
+    use cosmwasm_std::BankMsg;
+    use cw_utils::MsgInstantiateContractResponse;
+
+    use crate::state::{deals_map, PENDING_SWAP};
+
+    use super::*;
+
+    /// Handle the replies to the contract. A single reply coming from the positions contract
+    /// instantiation is expected.
+    pub fn handle_instantiate_reply(
+        deps: DepsMut,
+        res: MsgInstantiateContractResponse,
+    ) -> Result<Response, ContractError> {
+        let positions = deps.api.addr_validate(&res.contract_address)?;
+        CONFIG.update(deps.storage, |mut config| -> StdResult<_> {
+            config.positions = Some(positions);
+            Ok(config)
+        })?;
+        Ok(Response::new().add_attribute("positions", res.contract_address))
+    }
+
+    /// Handle the reply of the swap of a deal accepted with a different coin. The deal is accepted
+    /// with the requested coin received from the swap and the excess is refunded to the taker.
+    pub fn handle_swap_reply(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let pending = PENDING_SWAP.load(deps.storage)?;
+        PENDING_SWAP.remove(deps.storage);
+
+        let deal = deals_map().load(deps.storage, (&pending.creator, pending.deal_id))?;
+        let received = deps
+            .querier
+            .query_balance(&env.contract.address, &deal.coin_out.denom)?
+            .amount
+            .saturating_sub(pending.balance);
+        if received < deal.coin_out.amount {
+            return Err(ContractError::SwapBelowRequested {
+                received,
+                requested: deal.coin_out.amount,
+            });
+        }
+
+        let (deal, mut msgs) = execute::match_deal(
+            deps,
+            &env,
+            &config,
+            &pending.taker,
+            &pending.creator,
+            pending.deal_id,
+            &deal.coin_out,
+        )?;
+        let excess = received - deal.coin_out.amount;
+        if !excess.is_zero() {
+            msgs.push(
+                BankMsg::Send {
+                    to_address: pending.taker.to_string(),
+                    amount: vec![coin(excess.u128(), &deal.coin_out.denom)],
+                }
+                .into(),
+            );
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_deal")
+            .add_attribute("deal_counterparty", pending.taker)
+            .add_attribute("swap_received", received)
+            .add_messages(msgs))
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -373,7 +3379,7 @@ mod tests {
         Addr,
     };
 
-    use common::market::InstantiateMsg;
+    use common::market::{DealLimits, InstantiateMsg};
 
     use super::*;
 
@@ -391,18 +3397,40 @@ mod tests {
                 first_coin: "astro".to_owned(),
                 second_coin: "usdc".to_owned(),
                 fee: Decimal::percent(1),
+                positions_code_id: None,
+                limits: None,
+                group: None,
+                factory: None,
+                oracle: None,
+                other_coins: None,
+                router: None,
             },
         )
         .unwrap();
 
         let config = CONFIG.load(deps.as_ref().storage).unwrap();
         let expected_config = Config {
-            owner: Addr::unchecked("stepit"),
             first_coin: "astro".to_owned(),
             second_coin: "usdc".to_owned(),
             fee: Decimal::percent(1),
+            positions: None,
+            limits: DealLimits::default(),
+            group: None,
+            factory: None,
+            oracle: None,
+            other_coins: vec![],
+            router: None,
         };
-        assert_eq!(expected_config, config, "expected different config")
+        assert_eq!(expected_config, config, "expected different config");
+
+        let owner = cw_ownable::get_ownership(deps.as_ref().storage)
+            .unwrap()
+            .owner;
+        assert_eq!(
+            Some(Addr::unchecked("stepit")),
+            owner,
+            "expected instantiator to be the owner"
+        )
     }
 
     #[test]
@@ -420,17 +3448,30 @@ mod tests {
                     .to_owned(),
                 second_coin: "usdc".to_owned(),
                 fee: Decimal::percent(1),
+                positions_code_id: None,
+                limits: None,
+                group: None,
+                factory: None,
+                oracle: None,
+                other_coins: None,
+                router: None,
             },
         )
         .unwrap();
 
         let config = CONFIG.load(deps.as_ref().storage).unwrap();
         let expected_config = Config {
-            owner: Addr::unchecked("stepit"),
             first_coin: "ibc/EBD5A24C554198EBAF44979C5B4D2C2D312E6EBAB71962C92F735499C7575839"
                 .to_owned(),
             second_coin: "usdc".to_owned(),
             fee: Decimal::percent(1),
+            positions: None,
+            limits: DealLimits::default(),
+            group: None,
+            factory: None,
+            oracle: None,
+            other_coins: vec![],
+            router: None,
         };
         assert_eq!(expected_config, config, "expected different config")
     }
@@ -449,16 +3490,29 @@ mod tests {
                 first_coin: "factory/wasm1jdppe6fnj2q7hjsepty5crxtrryzhuqsjrj95y/astro".to_owned(),
                 second_coin: "usdc".to_owned(),
                 fee: Decimal::percent(1),
+                positions_code_id: None,
+                limits: None,
+                group: None,
+                factory: None,
+                oracle: None,
+                other_coins: None,
+                router: None,
             },
         )
         .unwrap();
 
         let config = CONFIG.load(deps.as_ref().storage).unwrap();
         let expected_config = Config {
-            owner: Addr::unchecked("stepit"),
             first_coin: "factory/wasm1jdppe6fnj2q7hjsepty5crxtrryzhuqsjrj95y/astro".to_owned(),
             second_coin: "usdc".to_owned(),
             fee: Decimal::percent(1),
+            positions: None,
+            limits: DealLimits::default(),
+            group: None,
+            factory: None,
+            oracle: None,
+            other_coins: vec![],
+            router: None,
         };
         assert_eq!(expected_config, config, "expected different config")
     }
@@ -477,6 +3531,13 @@ mod tests {
                 first_coin: "astro".to_owned(),
                 second_coin: "usdc".to_owned(),
                 fee: Decimal::percent(6),
+                positions_code_id: None,
+                limits: None,
+                group: None,
+                factory: None,
+                oracle: None,
+                other_coins: None,
+                router: None,
             },
         )
         .unwrap_err();
@@ -489,12 +3550,19 @@ mod tests {
 
         let err = instantiate(
             deps.as_mut(),
-            env,
-            info,
+            env.clone(),
+            info.clone(),
             InstantiateMsg {
                 first_coin: "astro".to_owned(),
                 second_coin: "astro".to_owned(),
                 fee: Decimal::percent(1),
+                positions_code_id: None,
+                limits: None,
+                group: None,
+                factory: None,
+                oracle: None,
+                other_coins: None,
+                router: None,
             },
         )
         .unwrap_err();
@@ -507,5 +3575,45 @@ mod tests {
             },
             "expected different error for same coin"
         );
+
+        let err = instantiate(
+            deps.as_mut(),
+            env,
+            info,
+            InstantiateMsg {
+                first_coin: "astro".to_owned(),
+                second_coin: "usdc".to_owned(),
+                fee: Decimal::percent(1),
+                positions_code_id: None,
+                limits: None,
+                group: None,
+                factory: None,
+                oracle: None,
+                other_coins: Some(vec!["usdt".to_owned(), "usdc".to_owned()]),
+                router: None,
+            },
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            err,
+            ContractError::DuplicatedCoin {
+                denom: "usdc".to_owned()
+            },
+            "expected different error for duplicated coin"
+        );
+    }
+
+    #[test]
+    fn create_deal_without_alternatives_deserializes() {
+        let msg: ExecuteMsg = cosmwasm_std::from_json(
+            r#"{"create_deal":{"coin_out":{"denom":"usdc","amount":"100"},"counterparties":[],"group":null,"expiry":{"never":{}},"match_crossing":null,"kind":null,"trigger":null}}"#,
+        )
+        .unwrap();
+
+        assert!(
+            matches!(msg, ExecuteMsg::CreateDeal { coin_out_alternatives, .. } if coin_out_alternatives.is_empty()),
+            "expected alternatives to default to empty"
+        );
     }
 }
@@ -11,6 +11,9 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized,
 
+    #[error("{0}")]
+    Ownership(#[from] cw_ownable::OwnershipError),
+
     #[error("Market fee cannot exceeds maximum allowed of {} bps", MAX_FEE)]
     OverFeeMax {},
 
@@ -26,15 +29,141 @@ pub enum ContractError {
     #[error("Sent coin is not allowed")]
     CoinNotAllowed {},
 
+    #[error("Coin to buy must be specified for {denom}")]
+    MissingDenomOut { denom: String },
+
     #[error("Deal not available: expired or already matched")]
     DealNotAvailable {},
 
     #[error("Sent coins not allowed. Expected {amount}{denom}")]
     WrongCoin { denom: String, amount: Uint128 },
 
-    #[error("The deal ")]
+    #[error("The deal is not matched or already completed")]
     DealNotMatched {},
 
     #[error("Creator cannot accept the deal")]
     SenderIsCreator {},
+
+    #[error("No public key registered to verify offers of {maker}")]
+    SignerNotRegistered { maker: String },
+
+    #[error("Offer signature verification failed")]
+    InvalidSignature {},
+
+    #[error("Offer not valid for this market")]
+    WrongMarket {},
+
+    #[error("Offer not valid for this chain")]
+    WrongChain {},
+
+    #[error("Offer expired")]
+    OfferExpired {},
+
+    #[error("Offer nonce {nonce} already used")]
+    NonceAlreadyUsed { nonce: u64 },
+
+    #[error("Insufficient balance. Available {amount}{denom}")]
+    InsufficientBalance { denom: String, amount: Uint128 },
+
+    #[error("Batch must contain at least one deal")]
+    EmptyBatch {},
+
+    #[error("Sent coins not allowed. Expected {expected}")]
+    WrongFunds { expected: String },
+
+    #[error("Position has already been withdrawn")]
+    PositionWithdrawn {},
+
+    #[error("Positions are tokenized and must be transferred through the positions contract")]
+    PositionsTokenized {},
+
+    #[error("Unknown reply ID")]
+    UnknownReply {},
+
+    #[error("Received amount {received} is lower than the minimum {min_receive}")]
+    MinReceiveNotMet {
+        received: Uint128,
+        min_receive: Uint128,
+    },
+
+    #[error("Amount cannot be zero")]
+    ZeroAmount {},
+
+    #[error("Deal terms do not match the expected ones")]
+    DealTermsChanged {},
+
+    #[error("Deal expiry is already reached")]
+    InvalidExpiry {},
+
+    #[error("Invalid deal limits: minimum values cannot exceed maximum ones")]
+    InvalidLimits {},
+
+    #[error("Deal expiry must be expressed in the same unit of the market duration limits")]
+    ExpiryUnitMismatch {},
+
+    #[error("Deal duration is lower than the market minimum")]
+    DurationBelowMinimum {},
+
+    #[error("Deal duration is higher than the market maximum")]
+    DurationAboveMaximum {},
+
+    #[error("Amount of {denom} is lower than the market minimum of {min}")]
+    AmountBelowMinimum { denom: String, min: Uint128 },
+
+    #[error("Amount of {denom} is higher than the market maximum of {max}")]
+    AmountAboveMaximum { denom: String, max: Uint128 },
+
+    #[error("A deal can be reserved to at most {max} counterparties")]
+    TooManyCounterparties { max: usize },
+
+    #[error("{addr} is not a member of the group")]
+    NotGroupMember { addr: String },
+
+    #[error("A deal can be reserved either to counterparties or to a group")]
+    CounterpartiesAndGroup {},
+
+    #[error("{addr} is denied")]
+    Denied { addr: String },
+
+    #[error("Operation not supported by the kind of the deal")]
+    WrongDealKind {},
+
+    #[error("Collateral must be higher than zero and not higher than one")]
+    InvalidCollateral {},
+
+    #[error("Deal settlement is already reached")]
+    InvalidSettlement {},
+
+    #[error("Deal settlement is not reached yet")]
+    SettlementNotReached {},
+
+    #[error("Deal settlement is reached and cannot be funded anymore")]
+    SettlementReached {},
+
+    #[error("Interval of recurring deals cannot be zero")]
+    InvalidInterval {},
+
+    #[error("Market has no oracle to check the price trigger")]
+    MissingOracle {},
+
+    #[error("Deal price trigger is not reached")]
+    TriggerNotReached {},
+
+    #[error("OCO group must contain at least two open deals not already grouped")]
+    InvalidOcoGroup {},
+
+    #[error("Coin {denom} is listed more than once in the market")]
+    DuplicatedCoin { denom: String },
+
+    #[error("Market has no router to swap the sent coin")]
+    MissingRouter {},
+
+    #[error("Sent coin is the one requested by the deal and does not require a swap")]
+    SwapNotRequired {},
+
+    #[error("Swap returned {received} that is lower than the requested {requested}")]
+    SwapBelowRequested {
+        received: Uint128,
+        requested: Uint128,
+    },
 }
@@ -1,18 +1,38 @@
-use common::{factory::Config, market::Deal};
+use common::{
+    factory::Config,
+    market::{Deal, DealKind, DealLimits, DealStatus, Offer, PriceTrigger, Side},
+};
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Coin};
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Uint128};
+use cw_ownable::{cw_ownable_execute, cw_ownable_query};
+use cw_utils::{Expiration, Scheduled};
 
 /// This enum describes available contract's execution messages.
+#[cw_ownable_execute]
 #[cw_serde]
 pub enum ExecuteMsg {
     /// Allows to update the contract's configuration. Only owner can update.
     CreateDeal {
         /// Coin that the user wants to receive.
         coin_out: Coin,
-        /// If specified, is the only counterparty accepted in the deal.
-        counterparty: Option<String>,
-        /// Duration in blocks for the deal.
-        timeout: u64,
+        /// If not empty, are the only counterparties allowed to accept the deal.
+        counterparties: Vec<String>,
+        /// If specified, only the members of this CW4 group can accept the deal. Cannot be used
+        /// together with the counterparties.
+        group: Option<String>,
+        /// Height, time, or never after which the deal expires.
+        expiry: Expiration,
+        /// If true, the deal is first matched against the open deals of the other side of the
+        /// market with a price equal or better than its own. Only the amount left is stored.
+        match_crossing: Option<bool>,
+        /// Kind of the deal. Default to a spot deal.
+        kind: Option<DealKind>,
+        /// If specified, the deal can be accepted only when the oracle price satisfies the
+        /// trigger.
+        trigger: Option<PriceTrigger>,
+        /// Coins accepted instead of the requested one. The counterparty can send any of them.
+        #[serde(default)]
+        coin_out_alternatives: Vec<Coin>,
     },
     /// Allows to accept a deal.
     AcceptDeal {
@@ -20,6 +40,53 @@ pub enum ExecuteMsg {
         creator: String,
         /// Coin that the user wants to exchange for.
         deal_id: u64,
+        /// If specified, the coin offered by the deal must be equal to this one.
+        expected_coin_in: Option<Coin>,
+        /// If specified, the amount received after fee must be at least this one.
+        min_receive: Option<Uint128>,
+        /// If specified, the deal must not have been amended after this version.
+        expected_version: Option<u64>,
+    },
+    /// Allows to accept a deal paying with a coin different from the requested one. The sent coin is
+    /// swapped into the requested one through the market router, the deal is accepted once the swap
+    /// is completed, and the requested coin received in excess is refunded.
+    AcceptDealWithSwap {
+        /// Address of the deal creator.
+        creator: String,
+        /// Identifier of the deal.
+        deal_id: u64,
+        /// Maximum spread allowed for the swap.
+        max_spread: Option<Decimal>,
+        /// If specified, the coin offered by the deal must be equal to this one.
+        expected_coin_in: Option<Coin>,
+        /// If specified, the amount received after fee must be at least this one.
+        min_receive: Option<Uint128>,
+        /// If specified, the deal must not have been amended after this version.
+        expected_version: Option<u64>,
+    },
+    /// Allows the factory of the market to accept a deal on behalf of a taker, used to accept deals
+    /// of different markets atomically. The taker is the counterparty of the deal.
+    AcceptDealFor {
+        /// Address accepting the deal.
+        taker: String,
+        /// Address of the deal creator.
+        creator: String,
+        /// Identifier of the deal.
+        deal_id: u64,
+        /// If specified, the coin offered by the deal must be equal to this one.
+        expected_coin_in: Option<Coin>,
+        /// If specified, the amount received after fee must be at least this one.
+        min_receive: Option<Uint128>,
+        /// If specified, the deal must not have been amended after this version.
+        expected_version: Option<u64>,
+    },
+    /// Allows the holder of the creator positions to group open deals in a new one-cancels-other
+    /// group: when one of them is accepted, the others are cancelled and refunded.
+    LinkOco {
+        /// Address of the deals creator.
+        creator: String,
+        /// Identifiers of the deals to group.
+        deal_ids: Vec<u64>,
     },
     /// Allows to withdraw tokens associated with a deal.
     Withdraw {
@@ -28,6 +95,195 @@ pub enum ExecuteMsg {
         /// Coin that the user wants to exchange for.
         deal_id: u64,
     },
+    /// Allows a maker to deposit coins used to settle offers signed off-chain.
+    Deposit {},
+    /// Allows a maker to withdraw coins from their deposited balance.
+    WithdrawBalance {
+        /// Coin to withdraw from the balance.
+        coin: Coin,
+    },
+    /// Allows a maker to register the secp256k1 public key used to verify their offers. If not
+    /// specified, the registered key is removed.
+    SetOfferSigner { pubkey: Option<Binary> },
+    /// Allows to accept an offer signed off-chain by a maker.
+    AcceptOffer {
+        /// Terms of the offer.
+        offer: Offer,
+        /// Signature of the offer hash produced with the maker's registered key.
+        signature: Binary,
+    },
+    /// Allows a maker to invalidate offers not yet accepted.
+    CancelOffers {
+        /// Nonces of the offers to invalidate.
+        nonces: Vec<u64>,
+    },
+    /// Allows to create multiple deals in a single transaction. Sent funds must be equal to the
+    /// sum of the coins deposited in the deals.
+    BatchCreateDeals { deals: Vec<DealParams> },
+    /// Allows to accept multiple deals in a single transaction. Sent funds must be equal to the
+    /// sum of the coins requested by the deals.
+    BatchAcceptDeals { deals: Vec<DealKey> },
+    /// Allows to withdraw tokens associated with multiple deals in a single transaction.
+    BatchWithdraw { deals: Vec<DealKey> },
+    /// Allows anyone to complete a matched deal sending the coins not yet withdrawn to both the
+    /// creator and the counterparty.
+    Settle {
+        /// Address of the deal creator.
+        creator: String,
+        /// Identifier of the deal.
+        deal_id: u64,
+    },
+    /// Allows the holder of a deal position to transfer it to another address.
+    TransferPosition {
+        /// Address of the deal creator.
+        creator: String,
+        /// Identifier of the deal.
+        deal_id: u64,
+        /// Position to transfer.
+        side: Side,
+        /// Address receiving the position.
+        recipient: String,
+    },
+    /// Allows the holder of the creator position to amend a deal not yet matched. Increasing the
+    /// offered amount requires to send the difference, while decreasing it refunds the difference.
+    AmendDeal {
+        /// Address of the deal creator.
+        creator: String,
+        /// Identifier of the deal.
+        deal_id: u64,
+        /// New amount of the offered coin.
+        new_coin_in: Option<Uint128>,
+        /// New coin that the user wants to receive.
+        new_coin_out: Option<Coin>,
+        /// New height, time, or never after which the deal expires.
+        new_expiry: Option<Expiration>,
+        /// New counterparties of the deal, the only ones allowed to accept it. If empty, anyone
+        /// can accept the deal.
+        new_counterparties: Option<Vec<String>>,
+    },
+    /// Allows the owner to update the limits applied to new and amended deals.
+    UpdateLimits { limits: DealLimits },
+    /// Allows to buy the other coin of the market from the cheapest open deals until the sent coin
+    /// is spent. Deals can be partially filled and the amount not spent is refunded.
+    MarketTake {
+        /// Maximum amount of the sent coin paid for one unit of the other coin.
+        max_price: Decimal,
+        /// Minimum amount of the other coin to receive, after fee.
+        min_receive: Uint128,
+        /// Coin to buy. Required only if the sent coin is not the first or the second coin of the
+        /// market, which are bought one with the other.
+        denom_out: Option<String>,
+    },
+    /// Allows to buy an option deal sending its premium, which is paid to the creator.
+    BuyOption {
+        /// Address of the deal creator.
+        creator: String,
+        /// Identifier of the deal.
+        deal_id: u64,
+    },
+    /// Allows the holder of a bought option to exercise it before expiry sending the coin
+    /// requested by the deal.
+    ExerciseOption {
+        /// Address of the deal creator.
+        creator: String,
+        /// Identifier of the deal.
+        deal_id: u64,
+    },
+    /// Allows to create a forward deal. Sent funds must be equal to the collateral of the offered
+    /// coin.
+    CreateForward {
+        /// Coin that the user wants to swap at settlement.
+        coin_in: Coin,
+        /// Coin that the user wants to receive at settlement.
+        coin_out: Coin,
+        /// If not empty, are the only counterparties allowed to accept the deal.
+        counterparties: Vec<String>,
+        /// If specified, only the members of this CW4 group can accept the deal.
+        group: Option<String>,
+        /// Height, time, or never after which the deal cannot be accepted.
+        expiry: Expiration,
+        /// Height or time after which the deal can be settled.
+        settle_at: Scheduled,
+        /// Fraction of the coins locked when the deal is agreed.
+        collateral: Decimal,
+    },
+    /// Allows to agree a forward deal. Sent funds must be equal to the collateral of the requested
+    /// coin.
+    AcceptForward {
+        /// Address of the deal creator.
+        creator: String,
+        /// Identifier of the deal.
+        deal_id: u64,
+    },
+    /// Allows the parties of an agreed forward to deposit the rest of their coin before the
+    /// settlement.
+    FundForward {
+        /// Address of the deal creator.
+        creator: String,
+        /// Identifier of the deal.
+        deal_id: u64,
+    },
+    /// Allows anyone to settle an agreed forward after the settlement height or time.
+    SettleForward {
+        /// Address of the deal creator.
+        creator: String,
+        /// Identifier of the deal.
+        deal_id: u64,
+    },
+    /// Allows to fill the available tranche of a recurring deal. Sent funds must be equal to the
+    /// requested coin of a tranche, multiplied by the available tranches.
+    AcceptTranche {
+        /// Address of the deal creator.
+        creator: String,
+        /// Identifier of the deal.
+        deal_id: u64,
+    },
+}
+
+/// Parameters of a deal created in a batch.
+#[cw_serde]
+pub struct DealParams {
+    /// Coin that the user wants to swap.
+    pub coin_in: Coin,
+    /// Coin that the user wants to receive.
+    pub coin_out: Coin,
+    /// If not empty, are the only counterparties allowed to accept the deal.
+    pub counterparties: Vec<String>,
+    /// If specified, only the members of this CW4 group can accept the deal.
+    pub group: Option<String>,
+    /// Height, time, or never after which the deal expires.
+    pub expiry: Expiration,
+    /// Kind of the deal. Default to a spot deal.
+    pub kind: Option<DealKind>,
+    /// If specified, the deal can be accepted only when the oracle price satisfies the trigger.
+    pub trigger: Option<PriceTrigger>,
+    /// Coins accepted instead of the requested one. The counterparty can send any of them.
+    #[serde(default)]
+    pub coin_out_alternatives: Vec<Coin>,
+}
+
+/// Identifies a deal in the market.
+#[cw_serde]
+pub struct DealKey {
+    /// Address of the deal creator.
+    pub creator: String,
+    /// Identifier of the deal.
+    pub deal_id: u64,
+}
+
+/// Result of a single deal processed in a batch.
+#[cw_serde]
+pub struct DealResult {
+    pub creator: Addr,
+    pub deal_id: u64,
+    /// Status of the deal after the execution.
+    pub status: DealStatus,
+}
+
+/// Data returned by batch execution messages.
+#[cw_serde]
+pub struct BatchResponse {
+    pub results: Vec<DealResult>,
 }
 
 #[cw_serde]
@@ -40,7 +296,49 @@ pub struct AllDealsResponse {
     pub deals: Vec<((Addr, u64), Deal)>,
 }
 
+/// Open deals at the same price.
+#[cw_serde]
+pub struct OrderBookLevel {
+    /// Amount of second coin for one first coin.
+    pub price: Decimal,
+    /// Total amount offered by the deals.
+    pub offered: Uint128,
+    /// Total amount requested by the deals.
+    pub requested: Uint128,
+    /// Number of deals in the level.
+    pub deals: u32,
+}
+
+#[cw_serde]
+pub struct OrderBookResponse {
+    /// Levels of the deals offering the first coin of the pair, lowest price first.
+    pub sell_first_coin: Vec<OrderBookLevel>,
+    /// Levels of the deals offering the second coin of the pair, highest price first.
+    pub sell_second_coin: Vec<OrderBookLevel>,
+}
+
+#[cw_serde]
+pub struct BalanceResponse {
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct OfferSignerResponse {
+    pub pubkey: Option<Binary>,
+}
+
+#[cw_serde]
+pub struct NonceUsedResponse {
+    pub used: bool,
+}
+
+#[cw_serde]
+pub struct OcoGroupResponse {
+    pub deals: Vec<((Addr, u64), Deal)>,
+}
+
 /// This enum describes available contract's query messages.
+#[cw_ownable_query]
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
@@ -53,4 +351,28 @@ pub enum QueryMsg {
     /// Retrieve all available deals.
     #[returns(AllDealsResponse)]
     AllDeals {},
+    /// Retrieve the open deals not reserved to counterparties or a group aggregated by price for a
+    /// pair of the market coins, by default the first and the second coin. At most depth price
+    /// levels are returned for each side.
+    #[returns(OrderBookResponse)]
+    OrderBook {
+        depth: Option<u32>,
+        first_denom: Option<String>,
+        second_denom: Option<String>,
+    },
+    /// Retrieve the balance deposited by a maker for the given denom.
+    #[returns(BalanceResponse)]
+    Balance { maker: String, denom: String },
+    /// Retrieve the public key registered by a maker to sign offers.
+    #[returns(OfferSignerResponse)]
+    OfferSigner { maker: String },
+    /// Retrieve if an offer nonce has already been used by a maker.
+    #[returns(NonceUsedResponse)]
+    NonceUsed { maker: String, nonce: u64 },
+    /// Retrieve the bytes that a maker has to hash and sign to create a valid offer.
+    #[returns(Binary)]
+    OfferSignBytes { offer: Offer },
+    /// Retrieve the deals of a one-cancels-other group.
+    #[returns(OcoGroupResponse)]
+    OcoGroup { group_id: u64 },
 }
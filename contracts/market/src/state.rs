@@ -1,6 +1,7 @@
-use common::market::{Config, Deal};
-use cosmwasm_std::{Addr, StdResult, Storage};
-use cw_storage_plus::{Item, Map};
+use common::market::{Config, Deal, DealKind, DealStatus};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Binary, Decimal, Empty, StdResult, Storage, Uint128};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 
 /// Retrieve the number of the next deal to be created and increment the counter by one.
 pub fn next_id(store: &mut dyn Storage) -> StdResult<u64> {
@@ -11,7 +12,94 @@ pub fn next_id(store: &mut dyn Storage) -> StdResult<u64> {
 
 /// Data structure used to store the number of created deals.
 pub const COUNTER: Item<u64> = Item::new("counter");
+
+/// Retrieve the identifier of the next one-cancels-other group, starting from one.
+pub fn next_oco_id(store: &mut dyn Storage) -> StdResult<u64> {
+    let id = OCO_COUNTER.may_load(store)?.unwrap_or_default() + 1;
+    OCO_COUNTER.save(store, &id)?;
+    Ok(id)
+}
+
+/// Data structure used to store the number of one-cancels-other groups.
+pub const OCO_COUNTER: Item<u64> = Item::new("oco_counter");
+
+/// Key of a deal in the order book: offered denom, price, and deal identifier.
+pub type BookKey = (String, (u128, u64));
+
+/// Indexes over the stored deals.
+pub struct DealIndexes<'a> {
+    /// Open deals grouped by the offered denom and sorted by price and creation order.
+    pub book: MultiIndex<'a, BookKey, Deal, (&'a Addr, u64)>,
+    /// Deals grouped by one-cancels-other group, with the deals not grouped under zero.
+    pub oco: MultiIndex<'a, u64, Deal, (&'a Addr, u64)>,
+}
+
+impl<'a> IndexList<Deal> for DealIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Deal>> + '_> {
+        let v: Vec<&dyn Index<Deal>> = vec![&self.book, &self.oco];
+        Box::new(v.into_iter())
+    }
+}
+
 /// Data structure used to store all deals.
-pub const DEALS: Map<(&Addr, u64), Deal> = Map::new("deals");
+pub fn deals_map<'a>() -> IndexedMap<'a, (&'a Addr, u64), Deal, DealIndexes<'a>> {
+    let indexes = DealIndexes {
+        book: MultiIndex::new(book_index, "deals", "deals__book"),
+        oco: MultiIndex::new(
+            |_pk, deal| deal.oco.unwrap_or_default(),
+            "deals",
+            "deals__oco",
+        ),
+    };
+    IndexedMap::new("deals", indexes)
+}
+
+/// Returns the order book key of a deal: offered denom, price, and deal identifier. Deals not
+/// part of the book, such as reserved or conditional ones, are grouped under an empty denom.
+pub fn book_index(pk: &[u8], deal: &Deal) -> BookKey {
+    if deal.status != DealStatus::NotMatched
+        || !deal.counterparties.is_empty()
+        || deal.group.is_some()
+        || deal.kind != DealKind::Spot
+        || deal.trigger.is_some()
+    {
+        return (String::new(), (0, 0));
+    }
+    let mut deal_id = [0u8; 8];
+    deal_id.copy_from_slice(&pk[pk.len() - 8..]);
+    (
+        deal.coin_in.denom.clone(),
+        (book_price(deal), u64::from_be_bytes(deal_id)),
+    )
+}
+
+/// Returns the atomics of the ratio between the requested and the offered amounts of a deal.
+pub fn book_price(deal: &Deal) -> u128 {
+    Decimal::checked_from_ratio(deal.coin_out.amount, deal.coin_in.amount)
+        .map(|price| price.atomics().u128())
+        .unwrap_or(u128::MAX)
+}
+
+/// Deal accepted with a swap, stored until the reply of the swap is handled.
+#[cw_serde]
+pub struct PendingSwap {
+    /// Address accepting the deal.
+    pub taker: Addr,
+    /// Address of the deal creator.
+    pub creator: Addr,
+    /// Identifier of the deal.
+    pub deal_id: u64,
+    /// Balance of the requested coin held by the market before the swap.
+    pub balance: Uint128,
+}
+
 /// Single object storing contract's configuration.
 pub const CONFIG: Item<Config> = Item::new("config");
+/// Data structure used to store coins deposited by makers to settle signed offers.
+pub const BALANCES: Map<(&Addr, &str), Uint128> = Map::new("balances");
+/// Data structure used to store the public key used to verify offers signed by a maker.
+pub const OFFER_SIGNERS: Map<&Addr, Binary> = Map::new("offer_signers");
+/// Data structure used to store nonces of offers accepted or cancelled by a maker.
+pub const USED_NONCES: Map<(&Addr, u64), Empty> = Map::new("used_nonces");
+/// Data structure used to store the deal accepted with a swap during the swap execution.
+pub const PENDING_SWAP: Item<PendingSwap> = Item::new("pending_swap");
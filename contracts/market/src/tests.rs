@@ -1,3 +1,22 @@
 mod accept_deal;
+mod accept_offer;
+mod alternatives;
+mod amend_deal;
+mod batch;
 mod create_deal;
+mod crossing;
+mod forward;
+mod group;
+mod limits;
+mod market_take;
+mod oco;
+mod option;
+mod order_book;
+mod ownership;
+mod positions;
+mod recurring;
+mod settle;
+mod swap;
+mod transfer_position;
+mod trigger;
 mod withdraw;
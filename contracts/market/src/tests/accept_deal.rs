@@ -1,7 +1,12 @@
 use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
 use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
 
-use crate::{error::ContractError, msg::ExecuteMsg};
+use crate::{
+    contract::MAX_COUNTERPARTIES,
+    error::ContractError,
+    msg::{DealsByCreatorResponse, ExecuteMsg, QueryMsg},
+};
 
 use common::market::InstantiateMsg;
 
@@ -31,6 +36,13 @@ fn accept_deal_without_counterparty_works() {
         first_coin: "astro".to_string(),
         second_coin: "usdc".to_string(),
         fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
     };
     let market_addr = app
         .instantiate_contract(
@@ -64,8 +76,13 @@ fn accept_deal_without_counterparty_works() {
     // Create first deal
     let create_deal_msg = ExecuteMsg::CreateDeal {
         coin_out: Coin::new(1_000, "usdc"),
-        counterparty: None,
-        timeout: 10,
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
     };
     app.execute_contract(
         not_a_scammer.clone(),
@@ -79,6 +96,9 @@ fn accept_deal_without_counterparty_works() {
     let accept_deal_msg = ExecuteMsg::AcceptDeal {
         creator: not_a_scammer.to_string(),
         deal_id: 0,
+        expected_coin_in: None,
+        min_receive: None,
+        expected_version: None,
     };
     app.execute_contract(
         stepit.clone(),
@@ -103,6 +123,13 @@ fn accept_deal_with_counterparty_works() {
         first_coin: "astro".to_string(),
         second_coin: "usdc".to_string(),
         fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
     };
     let market_addr = app
         .instantiate_contract(
@@ -136,8 +163,13 @@ fn accept_deal_with_counterparty_works() {
     // Create first deal
     let create_deal_msg = ExecuteMsg::CreateDeal {
         coin_out: Coin::new(1_000, "usdc"),
-        counterparty: Some(stepit.to_string()),
-        timeout: 10,
+        counterparties: vec![stepit.to_string()],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
     };
     app.execute_contract(
         not_a_scammer.clone(),
@@ -151,6 +183,9 @@ fn accept_deal_with_counterparty_works() {
     let accept_deal_msg = ExecuteMsg::AcceptDeal {
         creator: not_a_scammer.to_string(),
         deal_id: 0,
+        expected_coin_in: None,
+        min_receive: None,
+        expected_version: None,
     };
     app.execute_contract(
         stepit.clone(),
@@ -176,6 +211,13 @@ fn accept_deal_error_handling() {
         first_coin: "astro".to_string(),
         second_coin: "usdc".to_string(),
         fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
     };
     let market_addr = app
         .instantiate_contract(
@@ -224,8 +266,13 @@ fn accept_deal_error_handling() {
     // Create first deal
     let create_deal_msg = ExecuteMsg::CreateDeal {
         coin_out: Coin::new(500, "usdc"),
-        counterparty: Some(stepit.to_string()),
-        timeout: 10,
+        counterparties: vec![stepit.to_string()],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
     };
     app.execute_contract(
         not_a_scammer.clone(),
@@ -244,6 +291,9 @@ fn accept_deal_error_handling() {
     let accept_deal_msg = ExecuteMsg::AcceptDeal {
         creator: not_a_scammer.to_string(),
         deal_id: 0,
+        expected_coin_in: None,
+        min_receive: None,
+        expected_version: None,
     };
     let err = app
         .execute_contract(
@@ -356,3 +406,361 @@ fn accept_deal_error_handling() {
         "expected error because deal matched"
     );
 }
+
+#[test]
+fn accept_deal_expected_terms_handling() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    // Store and instantiate the market contract.
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(
+            market_id,
+            owner.clone(),
+            &init_market_msg,
+            &[],
+            "otc-market",
+            None,
+        )
+        .unwrap();
+
+    // Mint tokens to creator and counterparty
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "usdc")],
+    }))
+    .unwrap();
+
+    // Create a deal and reduce the offered amount
+    let create_deal_msg = ExecuteMsg::CreateDeal {
+        coin_out: Coin::new(1_000, "usdc"),
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
+    };
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &create_deal_msg,
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::AmendDeal {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+            new_coin_in: Some(Uint128::new(900)),
+            new_coin_out: None,
+            new_expiry: None,
+            new_counterparties: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let accept_deal_msg =
+        |expected_coin_in, min_receive, expected_version| ExecuteMsg::AcceptDeal {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+            expected_coin_in,
+            min_receive,
+            expected_version,
+        };
+
+    let failing_msgs = [
+        accept_deal_msg(Some(Coin::new(1_000, "astro")), None, None),
+        accept_deal_msg(None, Some(Uint128::new(900)), None),
+        accept_deal_msg(None, None, Some(0)),
+    ];
+    for msg in failing_msgs {
+        let err = app
+            .execute_contract(
+                stepit.clone(),
+                market_addr.clone(),
+                &msg,
+                &[Coin::new(1_000, "usdc")],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ContractError>().unwrap(),
+            &ContractError::DealTermsChanged {},
+            "expected error because the deal has been amended"
+        );
+    }
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr,
+        &accept_deal_msg(
+            Some(Coin::new(900, "astro")),
+            Some(Uint128::new(882)),
+            Some(1),
+        ),
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+}
+
+#[test]
+fn accept_deal_with_multiple_counterparties_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+    let desk = Addr::unchecked("0xdesk".to_string());
+
+    // Store and instantiate the market contract.
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(
+            market_id,
+            owner.clone(),
+            &init_market_msg,
+            &[],
+            "otc-market",
+            None,
+        )
+        .unwrap();
+
+    // Mint tokens to creator and counterparties
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(2_000, "astro")],
+    }))
+    .unwrap();
+    for addr in [&stepit, &desk, &owner] {
+        app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: addr.to_string(),
+            amount: vec![Coin::new(1_000, "usdc")],
+        }))
+        .unwrap();
+    }
+
+    // Deals cannot be reserved to too many counterparties
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::CreateDeal {
+                coin_out: Coin::new(1_000, "usdc"),
+                counterparties: (0..=MAX_COUNTERPARTIES)
+                    .map(|i| format!("0xdesk{i}"))
+                    .collect(),
+                group: None,
+                expiry: Expiration::AtHeight(app.block_info().height + 10),
+                match_crossing: None,
+                kind: None,
+                trigger: None,
+                coin_out_alternatives: vec![],
+            },
+            &[Coin::new(1_000, "astro")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::TooManyCounterparties {
+            max: MAX_COUNTERPARTIES
+        },
+        "expected error because too many counterparties"
+    );
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![desk.to_string(), stepit.to_string()],
+            group: None,
+            expiry: Expiration::AtHeight(app.block_info().height + 10),
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    let accept_deal_msg = ExecuteMsg::AcceptDeal {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+        expected_coin_in: None,
+        min_receive: None,
+        expected_version: None,
+    };
+    let err = app
+        .execute_contract(
+            owner,
+            market_addr.clone(),
+            &accept_deal_msg,
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::Unauthorized {},
+        "expected error because owner is not a counterparty"
+    );
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &accept_deal_msg,
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+
+    let resp: DealsByCreatorResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr,
+            &QueryMsg::DealsByCreator {
+                creator: not_a_scammer.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp.deals[0].1.counterparty,
+        Some(stepit),
+        "expected the acceptor to be the counterparty"
+    );
+}
+
+#[test]
+fn accept_deal_after_partial_fill_checks_version() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+    let desk = Addr::unchecked("0xdesk".to_string());
+
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(market_id, owner, &init_market_msg, &[], "otc-market", None)
+        .unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    for addr in [&stepit, &desk] {
+        app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: addr.to_string(),
+            amount: vec![Coin::new(1_000, "usdc")],
+        }))
+        .unwrap();
+    }
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::AtHeight(app.block_info().height + 10),
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    // A partial fill changes the deal terms and its version.
+    app.execute_contract(
+        desk,
+        market_addr.clone(),
+        &ExecuteMsg::MarketTake {
+            max_price: Decimal::one(),
+            min_receive: Uint128::zero(),
+            denom_out: None,
+        },
+        &[Coin::new(400, "usdc")],
+    )
+    .unwrap();
+
+    let accept_deal_msg = |expected_version| ExecuteMsg::AcceptDeal {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+        expected_coin_in: None,
+        min_receive: None,
+        expected_version: Some(expected_version),
+    };
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &accept_deal_msg(0),
+            &[Coin::new(600, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::DealTermsChanged {},
+        "expected error because the deal has been partially filled"
+    );
+
+    app.execute_contract(
+        stepit,
+        market_addr,
+        &accept_deal_msg(1),
+        &[Coin::new(600, "usdc")],
+    )
+    .unwrap();
+}
@@ -0,0 +1,485 @@
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Empty, Uint128};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
+use k256::ecdsa::{signature::Signer, Signature, SigningKey};
+
+use crate::{
+    error::ContractError,
+    msg::{BalanceResponse, ExecuteMsg, NonceUsedResponse, QueryMsg},
+};
+
+use common::market::{InstantiateMsg, Offer};
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+// Sign the offer with the given key as expected by the market.
+fn sign_offer(key: &SigningKey, offer: &Offer) -> Binary {
+    let bytes = crate::contract::offer_sign_bytes(offer).unwrap();
+    let signature: Signature = key.sign(bytes.as_slice());
+    Binary::from(signature.to_bytes().as_slice())
+}
+
+#[test]
+fn accept_offer_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+    let maker_key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+
+    // Store and instantiate the market contract.
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(
+            market_id,
+            owner.clone(),
+            &init_market_msg,
+            &[],
+            "otc-market",
+            None,
+        )
+        .unwrap();
+
+    // Mint tokens to maker and taker
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(2_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "usdc")],
+    }))
+    .unwrap();
+
+    // Maker deposits a balance and registers the signing key
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::Deposit {},
+        &[Coin::new(2_000, "astro")],
+    )
+    .unwrap();
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::SetOfferSigner {
+            pubkey: Some(Binary::from(
+                maker_key.verifying_key().to_sec1_bytes().to_vec(),
+            )),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let offer = Offer {
+        chain_id: app.block_info().chain_id,
+        market: market_addr.to_string(),
+        maker: not_a_scammer.to_string(),
+        coin_in: Coin::new(1_000, "astro"),
+        coin_out: Coin::new(1_000, "usdc"),
+        counterparty: None,
+        nonce: 0,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+    };
+    let accept_offer_msg = ExecuteMsg::AcceptOffer {
+        signature: sign_offer(&maker_key, &offer),
+        offer,
+    };
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &accept_offer_msg,
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+
+    let taker_balance = app.wrap().query_balance(stepit.clone(), "astro").unwrap();
+    assert_eq!(
+        taker_balance.amount,
+        Uint128::new(980),
+        "expected taker to receive astro less the fee"
+    );
+    let maker_balance = app
+        .wrap()
+        .query_balance(not_a_scammer.clone(), "usdc")
+        .unwrap();
+    assert_eq!(
+        maker_balance.amount,
+        Uint128::new(980),
+        "expected maker to receive usdc less the fee"
+    );
+
+    let resp: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr.clone(),
+            &QueryMsg::Balance {
+                maker: not_a_scammer.to_string(),
+                denom: "astro".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp.amount,
+        Uint128::new(1_000),
+        "expected maker balance to be debited"
+    );
+
+    let resp: NonceUsedResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr.clone(),
+            &QueryMsg::NonceUsed {
+                maker: not_a_scammer.to_string(),
+                nonce: 0,
+            },
+        )
+        .unwrap();
+    assert!(resp.used, "expected nonce to be used");
+
+    // Maker withdraws the remaining balance
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::WithdrawBalance {
+            coin: Coin::new(1_000, "astro"),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let maker_balance = app
+        .wrap()
+        .query_balance(not_a_scammer.clone(), "astro")
+        .unwrap();
+    assert_eq!(
+        maker_balance.amount,
+        Uint128::new(1_000),
+        "expected maker to withdraw the balance"
+    );
+}
+
+#[test]
+fn accept_offer_handle_errors() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+    let maker_key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+    let other_key = SigningKey::from_slice(&[8u8; 32]).unwrap();
+
+    // Store and instantiate the market contract.
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(
+            market_id,
+            owner.clone(),
+            &init_market_msg,
+            &[],
+            "otc-market",
+            None,
+        )
+        .unwrap();
+
+    // Mint tokens to maker and taker
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(3_000, "usdc")],
+    }))
+    .unwrap();
+
+    let mut offer = Offer {
+        chain_id: app.block_info().chain_id,
+        market: market_addr.to_string(),
+        maker: not_a_scammer.to_string(),
+        coin_in: Coin::new(1_000, "astro"),
+        coin_out: Coin::new(1_000, "usdc"),
+        counterparty: None,
+        nonce: 0,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+    };
+
+    let accept_offer_msg = ExecuteMsg::AcceptOffer {
+        signature: sign_offer(&maker_key, &offer),
+        offer: offer.clone(),
+    };
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &accept_offer_msg,
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::SignerNotRegistered {
+            maker: not_a_scammer.to_string()
+        },
+        "expected error because maker has no registered key"
+    );
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::SetOfferSigner {
+            pubkey: Some(Binary::from(
+                maker_key.verifying_key().to_sec1_bytes().to_vec(),
+            )),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let accept_offer_msg = ExecuteMsg::AcceptOffer {
+        signature: sign_offer(&other_key, &offer),
+        offer: offer.clone(),
+    };
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &accept_offer_msg,
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::InvalidSignature {},
+        "expected error because offer signed with another key"
+    );
+
+    let accept_offer_msg = ExecuteMsg::AcceptOffer {
+        signature: sign_offer(&maker_key, &offer),
+        offer: offer.clone(),
+    };
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &accept_offer_msg,
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::InsufficientBalance {
+            denom: "astro".to_string(),
+            amount: Uint128::zero()
+        },
+        "expected error because maker has no balance"
+    );
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::Deposit {},
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    // Offers signed for another chain cannot be replayed
+    let other_chain_offer = Offer {
+        chain_id: "other-chain".to_string(),
+        ..offer.clone()
+    };
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::AcceptOffer {
+                signature: sign_offer(&maker_key, &other_chain_offer),
+                offer: other_chain_offer,
+            },
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::WrongChain {},
+        "expected error because offer signed for another chain"
+    );
+
+    // Let the offer expire
+    app.update_block(|block| {
+        block.height += 11;
+        block.time = block.time.plus_seconds(11 * 5);
+    });
+
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &accept_offer_msg,
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::OfferExpired {},
+        "expected error because offer expired"
+    );
+
+    // Time machine to go back when offer is not expired
+    app.update_block(|block| {
+        block.height -= 11;
+        block.time = block.time.minus_seconds(11 * 5);
+    });
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &accept_offer_msg,
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+
+    // Deposit again to verify that only the nonce prevents the replay
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::Deposit {},
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &accept_offer_msg,
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::NonceAlreadyUsed { nonce: 0 },
+        "expected error because offer already accepted"
+    );
+
+    // Cancelled offers cannot be accepted
+    offer.nonce = 1;
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::CancelOffers { nonces: vec![1] },
+        &[],
+    )
+    .unwrap();
+
+    let accept_offer_msg = ExecuteMsg::AcceptOffer {
+        signature: sign_offer(&maker_key, &offer),
+        offer: offer.clone(),
+    };
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &accept_offer_msg,
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::NonceAlreadyUsed { nonce: 1 },
+        "expected error because offer cancelled"
+    );
+
+    // Signed terms cannot be changed by the taker
+    offer.nonce = 2;
+    let signature = sign_offer(&maker_key, &offer);
+    offer.coin_in = Coin::new(900, "astro");
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::AcceptOffer {
+                signature,
+                offer: offer.clone(),
+            },
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::InvalidSignature {},
+        "expected error because offer terms changed"
+    );
+
+    // Offers signed for another market are rejected
+    offer.coin_in = Coin::new(1_000, "astro");
+    offer.market = "another_market".to_string();
+    let accept_offer_msg = ExecuteMsg::AcceptOffer {
+        signature: sign_offer(&maker_key, &offer),
+        offer,
+    };
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &accept_offer_msg,
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::WrongMarket {},
+        "expected error because offer signed for another market"
+    );
+}
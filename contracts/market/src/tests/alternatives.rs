@@ -0,0 +1,360 @@
+use cosmwasm_std::{coin, Addr, Coin, Decimal, Empty, Uint128};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
+
+use crate::{
+    error::ContractError,
+    msg::{DealsByCreatorResponse, ExecuteMsg, OrderBookResponse, QueryMsg},
+};
+
+use common::market::InstantiateMsg;
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+#[test]
+fn accept_deal_with_alternative_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: Some(vec!["usdt".to_string()]),
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(market_id, owner, &init_market_msg, &[], "otc-market", None)
+        .unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "usdt"), Coin::new(1_000, "ntrn")],
+    }))
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::CreateDeal {
+                coin_out: Coin::new(1_000, "usdc"),
+                counterparties: vec![],
+                group: None,
+                expiry: Expiration::AtHeight(app.block_info().height + 10),
+                match_crossing: None,
+                kind: None,
+                trigger: None,
+                coin_out_alternatives: vec![Coin::new(1_000, "ntrn")],
+            },
+            &[Coin::new(1_000, "astro")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::CoinNotAllowed {},
+        "expected error because the alternative is not a market coin"
+    );
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::AtHeight(app.block_info().height + 10),
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![Coin::new(990, "usdt")],
+        },
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    let resp: OrderBookResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr.clone(),
+            &QueryMsg::OrderBook {
+                depth: None,
+                first_denom: None,
+                second_denom: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp.sell_first_coin[0].requested,
+        Uint128::new(1_000),
+        "expected deal with alternatives to be part of the book for the requested coin"
+    );
+
+    let accept_deal_msg = ExecuteMsg::AcceptDeal {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+        expected_coin_in: None,
+        min_receive: None,
+        expected_version: None,
+    };
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &accept_deal_msg,
+            &[Coin::new(1_000, "usdt")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::WrongCoin {
+            denom: "usdc".to_string(),
+            amount: Uint128::new(1_000)
+        },
+        "expected error because the amount does not match any requested coin"
+    );
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &accept_deal_msg,
+        &[Coin::new(990, "usdt")],
+    )
+    .unwrap();
+
+    let resp: DealsByCreatorResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr,
+            &QueryMsg::DealsByCreator {
+                creator: not_a_scammer.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp.deals[0].1.coin_out,
+        coin(990, "usdt"),
+        "expected the alternative sent to become the requested coin"
+    );
+    assert!(resp.deals[0].1.coin_out_alternatives.is_empty());
+    assert_eq!(resp.deals[0].1.counterparty, Some(stepit));
+}
+
+#[test]
+fn market_take_deal_with_alternative_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: Some(vec!["usdt".to_string()]),
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(market_id, owner, &init_market_msg, &[], "otc-market", None)
+        .unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(500, "usdc"), Coin::new(500, "usdt")],
+    }))
+    .unwrap();
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::Never {},
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![Coin::new(990, "usdt")],
+        },
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::MarketTake {
+            max_price: Decimal::one(),
+            min_receive: Uint128::zero(),
+            denom_out: None,
+        },
+        &[Coin::new(500, "usdc")],
+    )
+    .unwrap();
+
+    let resp: DealsByCreatorResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr.clone(),
+            &QueryMsg::DealsByCreator {
+                creator: not_a_scammer.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp.deals[0].1.coin_out_alternatives,
+        vec![coin(495, "usdt")],
+        "expected alternatives reduced as the partially filled deal"
+    );
+
+    let err = app
+        .execute_contract(
+            stepit,
+            market_addr,
+            &ExecuteMsg::MarketTake {
+                max_price: Decimal::one(),
+                min_receive: Uint128::zero(),
+                denom_out: None,
+            },
+            &[Coin::new(500, "usdt")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::MissingDenomOut {
+            denom: "usdt".to_string()
+        },
+        "expected error because the coin to buy with usdt is ambiguous"
+    );
+}
+
+#[test]
+fn amend_deal_with_alternatives_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: Some(vec!["usdt".to_string()]),
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(market_id, owner, &init_market_msg, &[], "otc-market", None)
+        .unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::AtHeight(app.block_info().height + 10),
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![Coin::new(990, "usdt")],
+        },
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    let amend_deal_msg = |coin_in: Option<Uint128>, coin_out: Option<Coin>| ExecuteMsg::AmendDeal {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+        new_coin_in: coin_in,
+        new_coin_out: coin_out,
+        new_expiry: None,
+        new_counterparties: None,
+    };
+    let query_deal = |app: &App| {
+        let resp: DealsByCreatorResponse = app
+            .wrap()
+            .query_wasm_smart(
+                market_addr.clone(),
+                &QueryMsg::DealsByCreator {
+                    creator: not_a_scammer.to_string(),
+                },
+            )
+            .unwrap();
+        resp.deals[0].1.clone()
+    };
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &amend_deal_msg(Some(Uint128::new(500)), None),
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        query_deal(&app).coin_out_alternatives,
+        vec![coin(495, "usdt")],
+        "expected alternatives rescaled to the new coin deposited"
+    );
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &amend_deal_msg(None, Some(Coin::new(600, "usdc"))),
+        &[],
+    )
+    .unwrap();
+    assert!(
+        query_deal(&app).coin_out_alternatives.is_empty(),
+        "expected alternatives cleared with the new requested coin"
+    );
+}
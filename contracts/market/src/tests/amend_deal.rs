@@ -0,0 +1,308 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
+
+use crate::{
+    error::ContractError,
+    msg::{DealsByCreatorResponse, ExecuteMsg, QueryMsg},
+};
+
+use common::market::{InstantiateMsg, Side};
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+// Instantiates a market where 0xtrustme created a deal selling 1_000 astro for 1_000 usdc.
+fn setup_market(app: &mut App) -> Addr {
+    let owner = Addr::unchecked(OWNER);
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(market_id, owner, &init_market_msg, &[], "otc-market", None)
+        .unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(2_000, "astro")],
+    }))
+    .unwrap();
+
+    app.execute_contract(
+        not_a_scammer,
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::AtHeight(app.block_info().height + 10),
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    market_addr
+}
+
+#[test]
+fn amend_deal_works() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_market(&mut app);
+
+    // Top up the offered coin and reprice
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::AmendDeal {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+            new_coin_in: Some(Uint128::new(1_500)),
+            new_coin_out: Some(Coin::new(1_800, "usdc")),
+            new_expiry: Some(Expiration::AtHeight(app.block_info().height + 20)),
+            new_counterparties: None,
+        },
+        &[Coin::new(500, "astro")],
+    )
+    .unwrap();
+
+    let resp: DealsByCreatorResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr.clone(),
+            &QueryMsg::DealsByCreator {
+                creator: not_a_scammer.to_string(),
+            },
+        )
+        .unwrap();
+    let (deal_id, deal) = &resp.deals[0];
+    assert_eq!(*deal_id, 0, "expected deal to keep its identifier");
+    assert_eq!(deal.coin_in, Coin::new(1_500, "astro"));
+    assert_eq!(deal.coin_out, Coin::new(1_800, "usdc"));
+    assert_eq!(
+        deal.expiry,
+        Expiration::AtHeight(app.block_info().height + 20)
+    );
+    assert_eq!(deal.version, 1, "expected version to be incremented");
+
+    // Partially refund the offered coin and reserve the deal
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::AmendDeal {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+            new_coin_in: Some(Uint128::new(800)),
+            new_coin_out: None,
+            new_expiry: None,
+            new_counterparties: Some(vec![stepit.to_string()]),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let resp: DealsByCreatorResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr,
+            &QueryMsg::DealsByCreator {
+                creator: not_a_scammer.to_string(),
+            },
+        )
+        .unwrap();
+    let deal = &resp.deals[0].1;
+    assert_eq!(deal.coin_in, Coin::new(800, "astro"));
+    assert_eq!(deal.counterparties, vec![stepit]);
+    assert_eq!(deal.version, 2, "expected version to be incremented");
+
+    let creator_balance = app.wrap().query_balance(not_a_scammer, "astro").unwrap();
+    assert_eq!(
+        creator_balance.amount,
+        Uint128::new(1_200),
+        "expected creator to receive back the difference"
+    );
+}
+
+#[test]
+fn amend_deal_handle_errors() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_market(&mut app);
+
+    let amend_msg = |new_coin_in: u128| ExecuteMsg::AmendDeal {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+        new_coin_in: Some(Uint128::new(new_coin_in)),
+        new_coin_out: None,
+        new_expiry: None,
+        new_counterparties: None,
+    };
+
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &amend_msg(1_500),
+            &[Coin::new(400, "astro")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::WrongFunds {
+            expected: "500astro".to_string()
+        },
+        "expected error because top up does not match the difference"
+    );
+
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &amend_msg(500),
+            &[Coin::new(100, "astro")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::WrongFunds {
+            expected: "".to_string()
+        },
+        "expected error because funds sent while decreasing the amount"
+    );
+
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &amend_msg(0),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::ZeroAmount {},
+        "expected error because the deal cannot offer nothing"
+    );
+
+    // Only the holder of the creator position can amend the deal
+    let err = app
+        .execute_contract(stepit.clone(), market_addr.clone(), &amend_msg(500), &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::Unauthorized {},
+        "expected error because the sender does not hold the creator position"
+    );
+
+    // Matched deals cannot be amended
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "usdc")],
+    }))
+    .unwrap();
+    app.execute_contract(
+        stepit,
+        market_addr.clone(),
+        &ExecuteMsg::AcceptDeal {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+            expected_coin_in: None,
+            min_receive: None,
+            expected_version: None,
+        },
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(not_a_scammer.clone(), market_addr, &amend_msg(500), &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::DealNotAvailable {},
+        "expected error because the deal is matched"
+    );
+}
+
+#[test]
+fn amend_transferred_deal_works() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_market(&mut app);
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::TransferPosition {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+            side: Side::Creator,
+            recipient: stepit.to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let amend_msg = ExecuteMsg::AmendDeal {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+        new_coin_in: Some(Uint128::new(600)),
+        new_coin_out: None,
+        new_expiry: None,
+        new_counterparties: None,
+    };
+    let err = app
+        .execute_contract(not_a_scammer.clone(), market_addr.clone(), &amend_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::Unauthorized {},
+        "expected error because the creator transferred the position"
+    );
+
+    app.execute_contract(stepit.clone(), market_addr.clone(), &amend_msg, &[])
+        .unwrap();
+    assert_eq!(
+        app.wrap()
+            .query_balance(&stepit, "astro")
+            .unwrap()
+            .amount
+            .u128(),
+        400,
+        "expected the difference refunded to the position holder"
+    );
+}
@@ -0,0 +1,340 @@
+use cosmwasm_std::{from_json, Addr, Coin, Decimal, Empty, Uint128};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
+
+use crate::{
+    error::ContractError,
+    msg::{AllDealsResponse, BatchResponse, DealKey, DealParams, ExecuteMsg, QueryMsg},
+};
+
+use common::market::{DealStatus, InstantiateMsg};
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+#[test]
+fn batch_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    // Store and instantiate the market contract.
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(0),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(
+            market_id,
+            owner.clone(),
+            &init_market_msg,
+            &[],
+            "otc-market",
+            None,
+        )
+        .unwrap();
+
+    // Mint tokens to creator and counterparty
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro"), Coin::new(500, "usdc")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(2_000, "astro"), Coin::new(1_000, "usdc")],
+    }))
+    .unwrap();
+
+    // Create three deals in both directions
+    let batch_create_msg = ExecuteMsg::BatchCreateDeals {
+        deals: vec![
+            DealParams {
+                coin_in: Coin::new(600, "astro"),
+                coin_out: Coin::new(600, "usdc"),
+                counterparties: vec![],
+                group: None,
+                expiry: Expiration::AtHeight(app.block_info().height + 10),
+                kind: None,
+                trigger: None,
+                coin_out_alternatives: vec![],
+            },
+            DealParams {
+                coin_in: Coin::new(400, "astro"),
+                coin_out: Coin::new(400, "usdc"),
+                counterparties: vec![stepit.to_string()],
+                group: None,
+                expiry: Expiration::AtHeight(app.block_info().height + 10),
+                kind: None,
+                trigger: None,
+                coin_out_alternatives: vec![],
+            },
+            DealParams {
+                coin_in: Coin::new(500, "usdc"),
+                coin_out: Coin::new(2_000, "astro"),
+                counterparties: vec![],
+                group: None,
+                expiry: Expiration::AtHeight(app.block_info().height + 10),
+                kind: None,
+                trigger: None,
+                coin_out_alternatives: vec![],
+            },
+        ],
+    };
+    let resp = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &batch_create_msg,
+            &[Coin::new(1_000, "astro"), Coin::new(500, "usdc")],
+        )
+        .unwrap();
+
+    let batch_resp: BatchResponse = from_json(resp.data.unwrap()).unwrap();
+    assert_eq!(
+        batch_resp
+            .results
+            .iter()
+            .map(|result| result.deal_id)
+            .collect::<Vec<u64>>(),
+        vec![0, 1, 2],
+        "expected three created deals"
+    );
+
+    // Accept all deals
+    let deal_keys: Vec<DealKey> = (0..3)
+        .map(|deal_id| DealKey {
+            creator: not_a_scammer.to_string(),
+            deal_id,
+        })
+        .collect();
+    let resp = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::BatchAcceptDeals {
+                deals: deal_keys.clone(),
+            },
+            &[Coin::new(2_000, "astro"), Coin::new(1_000, "usdc")],
+        )
+        .unwrap();
+
+    let batch_resp: BatchResponse = from_json(resp.data.unwrap()).unwrap();
+    assert!(
+        batch_resp
+            .results
+            .iter()
+            .all(|result| result.status == DealStatus::matched_no_withdraw()),
+        "expected all deals matched"
+    );
+
+    // Both parties withdraw all deals
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::BatchWithdraw {
+            deals: deal_keys.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+    let resp = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::BatchWithdraw { deals: deal_keys },
+            &[],
+        )
+        .unwrap();
+
+    let batch_resp: BatchResponse = from_json(resp.data.unwrap()).unwrap();
+    assert!(
+        batch_resp
+            .results
+            .iter()
+            .all(|result| result.status == DealStatus::matched_and_completed()),
+        "expected all deals completed"
+    );
+
+    let creator_balance = app.wrap().query_all_balances(not_a_scammer).unwrap();
+    assert_eq!(
+        creator_balance,
+        vec![Coin::new(2_000, "astro"), Coin::new(1_000, "usdc")],
+        "expected creator to receive counterparty coins"
+    );
+    let counterparty_balance = app.wrap().query_all_balances(stepit).unwrap();
+    assert_eq!(
+        counterparty_balance,
+        vec![Coin::new(1_000, "astro"), Coin::new(500, "usdc")],
+        "expected counterparty to receive creator coins"
+    );
+}
+
+#[test]
+fn batch_handle_errors() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    // Store and instantiate the market contract.
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(0),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(
+            market_id,
+            owner.clone(),
+            &init_market_msg,
+            &[],
+            "otc-market",
+            None,
+        )
+        .unwrap();
+
+    // Mint tokens to creator and counterparty
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "usdc")],
+    }))
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::BatchCreateDeals { deals: vec![] },
+            &[],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::EmptyBatch {},
+        "expected error because no deals"
+    );
+
+    let batch_create_msg = ExecuteMsg::BatchCreateDeals {
+        deals: vec![
+            DealParams {
+                coin_in: Coin::new(600, "astro"),
+                coin_out: Coin::new(600, "usdc"),
+                counterparties: vec![],
+                group: None,
+                expiry: Expiration::AtHeight(app.block_info().height + 10),
+                kind: None,
+                trigger: None,
+                coin_out_alternatives: vec![],
+            },
+            DealParams {
+                coin_in: Coin::new(400, "astro"),
+                coin_out: Coin::new(400, "usdc"),
+                counterparties: vec![not_a_scammer.to_string()],
+                group: None,
+                expiry: Expiration::AtHeight(app.block_info().height + 10),
+                kind: None,
+                trigger: None,
+                coin_out_alternatives: vec![],
+            },
+        ],
+    };
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &batch_create_msg,
+            &[Coin::new(900, "astro")],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::WrongFunds {
+            expected: "1000astro".to_string()
+        },
+        "expected error because sent funds are less than deposited in deals"
+    );
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &batch_create_msg,
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    let deal_keys: Vec<DealKey> = (0..2)
+        .map(|deal_id| DealKey {
+            creator: not_a_scammer.to_string(),
+            deal_id,
+        })
+        .collect();
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::BatchAcceptDeals { deals: deal_keys },
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::Unauthorized {},
+        "expected error because second deal reserved to another address"
+    );
+
+    let resp: AllDealsResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr.clone(), &QueryMsg::AllDeals {})
+        .unwrap();
+    assert!(
+        resp.deals
+            .iter()
+            .all(|(_, deal)| deal.status == DealStatus::NotMatched),
+        "expected no deal matched because batch is atomic"
+    );
+
+    let usdc_balance = app.wrap().query_balance(stepit, "usdc").unwrap();
+    assert_eq!(
+        usdc_balance.amount,
+        Uint128::new(1_000),
+        "expected sent funds to be returned"
+    );
+}
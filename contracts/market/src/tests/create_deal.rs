@@ -1,5 +1,6 @@
 use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
 use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
 
 use crate::{
     error::ContractError,
@@ -34,6 +35,13 @@ fn create_deal_works() {
         first_coin: "astro".to_string(),
         second_coin: "usdc".to_string(),
         fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
     };
     let market_addr = app
         .instantiate_contract(
@@ -65,8 +73,13 @@ fn create_deal_works() {
     // Create first deal
     let create_deal_msg = ExecuteMsg::CreateDeal {
         coin_out: Coin::new(1_000, "usdc"),
-        counterparty: None,
-        timeout: 10,
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
     };
     app.execute_contract(
         not_a_scammer.clone(),
@@ -79,8 +92,13 @@ fn create_deal_works() {
     // Create second deal with another account
     let create_deal_msg = ExecuteMsg::CreateDeal {
         coin_out: Coin::new(1_000, "usdc"),
-        counterparty: None,
-        timeout: 100,
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 100),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
     };
     app.execute_contract(
         stepit.clone(),
@@ -148,6 +166,13 @@ fn create_deal_handle_errors() {
         first_coin: "astro".to_string(),
         second_coin: "usdc".to_string(),
         fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
     };
     let market_addr = app
         .instantiate_contract(
@@ -180,8 +205,13 @@ fn create_deal_handle_errors() {
     // The deal is valid
     let create_deal_msg = ExecuteMsg::CreateDeal {
         coin_out: Coin::new(1_000, "usdc"),
-        counterparty: None,
-        timeout: 10,
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
     };
     let err = app
         .execute_contract(
@@ -200,8 +230,13 @@ fn create_deal_handle_errors() {
 
     let create_deal_msg = ExecuteMsg::CreateDeal {
         coin_out: Coin::new(1_000, "osmo"),
-        counterparty: None,
-        timeout: 100,
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 100),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
     };
     let err = app
         .execute_contract(
@@ -220,8 +255,13 @@ fn create_deal_handle_errors() {
 
     let create_deal_msg = ExecuteMsg::CreateDeal {
         coin_out: Coin::new(1_000, "astro"),
-        counterparty: None,
-        timeout: 100,
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 100),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
     };
     let err = app
         .execute_contract(
@@ -238,3 +278,146 @@ fn create_deal_handle_errors() {
         "expected error because sent coin not allowed"
     );
 }
+
+#[test]
+fn create_deal_with_time_expiry_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    // Store and instantiate the market contract.
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(
+            market_id,
+            owner.clone(),
+            &init_market_msg,
+            &[],
+            "otc-market",
+            None,
+        )
+        .unwrap();
+
+    // Mint tokens to creator and counterparty
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(3_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "usdc")],
+    }))
+    .unwrap();
+
+    // A deal valid for one hour and one that never expires
+    let expiring_time = app.block_info().time.plus_seconds(3_600);
+    for expiry in [Expiration::AtTime(expiring_time), Expiration::Never {}] {
+        app.execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::CreateDeal {
+                coin_out: Coin::new(1_000, "usdc"),
+                counterparties: vec![],
+                group: None,
+                expiry,
+                match_crossing: None,
+                kind: None,
+                trigger: None,
+                coin_out_alternatives: vec![],
+            },
+            &[Coin::new(1_000, "astro")],
+        )
+        .unwrap();
+    }
+
+    // Deals cannot be created already expired
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::CreateDeal {
+                coin_out: Coin::new(1_000, "usdc"),
+                counterparties: vec![],
+                group: None,
+                expiry: Expiration::AtTime(app.block_info().time),
+                match_crossing: None,
+                kind: None,
+                trigger: None,
+                coin_out_alternatives: vec![],
+            },
+            &[Coin::new(1_000, "astro")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::InvalidExpiry {},
+        "expected error because expiry already reached"
+    );
+
+    app.update_block(|block| {
+        block.height += 1;
+        block.time = block.time.plus_seconds(3_600);
+    });
+
+    let resp: AllDealsResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr.clone(), &QueryMsg::AllDeals {})
+        .unwrap();
+    assert_eq!(
+        resp.deals
+            .iter()
+            .map(|(key, _)| key.1)
+            .collect::<Vec<u64>>(),
+        vec![1],
+        "expected only the deal that never expires"
+    );
+
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::AcceptDeal {
+                creator: not_a_scammer.to_string(),
+                deal_id: 0,
+                expected_coin_in: None,
+                min_receive: None,
+                expected_version: None,
+            },
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::DealNotAvailable {},
+        "expected error because the deal is expired"
+    );
+
+    // The creator can still withdraw the expired deal
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr,
+        &ExecuteMsg::Withdraw {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+        },
+        &[],
+    )
+    .unwrap();
+    let creator_balance = app.wrap().query_balance(not_a_scammer, "astro").unwrap();
+    assert_eq!(creator_balance.amount, Uint128::new(2_000));
+}
@@ -0,0 +1,449 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
+
+use crate::msg::{AllDealsResponse, ExecuteMsg, QueryMsg};
+
+use common::market::{AmountLimits, DealLimits, InstantiateMsg, PriceTrigger};
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+// Instantiates an astro-usdc market with a 2% fee.
+fn setup_market(app: &mut App, limits: Option<DealLimits>, oracle: Option<String>) -> Addr {
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits,
+        group: None,
+        factory: None,
+        oracle,
+        other_coins: None,
+        router: None,
+    };
+    app.instantiate_contract(
+        market_id,
+        Addr::unchecked(OWNER),
+        &init_market_msg,
+        &[],
+        "otc-market",
+        None,
+    )
+    .unwrap()
+}
+
+// Returns the message to create a spot deal requesting coin_out.
+fn create_deal_msg(app: &App, coin_out: Coin, match_crossing: bool) -> ExecuteMsg {
+    ExecuteMsg::CreateDeal {
+        coin_out,
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: Some(match_crossing),
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
+    }
+}
+
+// Returns the identifiers of the stored deals in ascending order.
+fn deal_ids(app: &App, market_addr: &Addr) -> Vec<u64> {
+    let resp: AllDealsResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr, &QueryMsg::AllDeals {})
+        .unwrap();
+    let mut ids: Vec<u64> = resp.deals.iter().map(|(key, _)| key.1).collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn create_deal_match_crossing_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    // Store and instantiate the market contract.
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(
+            market_id,
+            owner.clone(),
+            &init_market_msg,
+            &[],
+            "otc-market",
+            None,
+        )
+        .unwrap();
+
+    // Mint tokens to the two creators
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(3_100, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(2_100, "usdc")],
+    }))
+    .unwrap();
+
+    // Deals selling astro at 1.0, 0.9, and 1.2 usdc
+    for usdc_amount in [1_000, 900, 1_200] {
+        app.execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::CreateDeal {
+                coin_out: Coin::new(usdc_amount, "usdc"),
+                counterparties: vec![],
+                group: None,
+                expiry: Expiration::AtHeight(app.block_info().height + 10),
+                match_crossing: None,
+                kind: None,
+                trigger: None,
+                coin_out_alternatives: vec![],
+            },
+            &[Coin::new(1_000, "astro")],
+        )
+        .unwrap();
+    }
+
+    // Buying astro at 1.05 usdc crosses the deals at 0.9 and 1.0 and the rest is stored
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(2_000, "astro"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::AtHeight(app.block_info().height + 10),
+            match_crossing: Some(true),
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(2_100, "usdc")],
+    )
+    .unwrap();
+
+    let resp: AllDealsResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr.clone(), &QueryMsg::AllDeals {})
+        .unwrap();
+    assert_eq!(
+        resp.deals
+            .iter()
+            .map(|(key, _)| key.1)
+            .collect::<Vec<u64>>(),
+        vec![3, 2],
+        "expected crossed deals to be removed and the new deal stored"
+    );
+    let new_deal = &resp.deals[0].1;
+    assert_eq!(
+        (new_deal.coin_in.clone(), new_deal.coin_out.clone()),
+        (Coin::new(200, "usdc"), Coin::new(190, "astro")),
+        "expected new deal to keep the amounts left at its price"
+    );
+
+    let stepit_astro = app.wrap().query_balance(stepit.clone(), "astro").unwrap();
+    assert_eq!(
+        stepit_astro.amount,
+        Uint128::new(1_960),
+        "expected creator to receive crossed astro less the fee"
+    );
+    let not_a_scammer_usdc = app
+        .wrap()
+        .query_balance(not_a_scammer.clone(), "usdc")
+        .unwrap();
+    assert_eq!(
+        not_a_scammer_usdc.amount,
+        Uint128::new(1_862),
+        "expected crossed creators to receive usdc less the fee"
+    );
+
+    // A deal fully crossed is not stored
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(100, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::AtHeight(app.block_info().height + 10),
+            match_crossing: Some(true),
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(100, "astro")],
+    )
+    .unwrap();
+
+    let resp: AllDealsResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr, &QueryMsg::AllDeals {})
+        .unwrap();
+    assert_eq!(
+        resp.deals
+            .iter()
+            .map(|(key, _)| key.1)
+            .collect::<Vec<u64>>(),
+        vec![3, 2],
+        "expected no new deal to be stored"
+    );
+    let partial_deal = &resp.deals[0].1;
+    assert_eq!(
+        (partial_deal.coin_in.clone(), partial_deal.coin_out.clone()),
+        (Coin::new(95, "usdc"), Coin::new(90, "astro")),
+        "expected crossed deal to be partially filled"
+    );
+
+    let stepit_astro = app.wrap().query_balance(stepit, "astro").unwrap();
+    assert_eq!(stepit_astro.amount, Uint128::new(2_058));
+    let not_a_scammer_usdc = app.wrap().query_balance(not_a_scammer, "usdc").unwrap();
+    assert_eq!(not_a_scammer_usdc.amount, Uint128::new(1_965));
+}
+
+#[test]
+fn match_crossing_price_time_priority_works() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+    let desk = Addr::unchecked("0xdesk".to_string());
+
+    let market_addr = setup_market(&mut app, None, None);
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: desk.to_string(),
+        amount: vec![Coin::new(500, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "usdc")],
+    }))
+    .unwrap();
+
+    // Deals selling astro at 1.0 by two creators, and a later one at 0.9.
+    for (creator, usdc_amount) in [(&not_a_scammer, 500), (&desk, 500), (&not_a_scammer, 450)] {
+        app.execute_contract(
+            creator.clone(),
+            market_addr.clone(),
+            &create_deal_msg(&app, Coin::new(usdc_amount, "usdc"), false),
+            &[Coin::new(500, "astro")],
+        )
+        .unwrap();
+    }
+
+    // The cheapest deal is filled first, then the deals at the same price by creation order.
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &create_deal_msg(&app, Coin::new(1_000, "astro"), true),
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+
+    assert_eq!(
+        deal_ids(&app, &market_addr),
+        vec![1],
+        "expected only the latest deal at the worst price to be left"
+    );
+    let resp: AllDealsResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr, &QueryMsg::AllDeals {})
+        .unwrap();
+    assert_eq!(
+        (
+            resp.deals[0].1.coin_in.clone(),
+            resp.deals[0].1.coin_out.clone()
+        ),
+        (Coin::new(450, "astro"), Coin::new(450, "usdc")),
+        "expected the latest deal at the same price to be partially filled"
+    );
+
+    assert_eq!(
+        app.wrap().query_balance(&stepit, "astro").unwrap().amount,
+        Uint128::new(1_029),
+        "expected creator to receive crossed astro less the fee"
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "usdc")
+            .unwrap()
+            .amount,
+        Uint128::new(931),
+        "expected the earliest creator to be fully filled"
+    );
+    assert_eq!(
+        app.wrap().query_balance(&desk, "usdc").unwrap().amount,
+        Uint128::new(49),
+        "expected the latest creator to be partially filled"
+    );
+}
+
+#[test]
+fn match_crossing_refunds_leftover_below_minimum() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let limits = DealLimits {
+        min_duration: None,
+        max_duration: None,
+        amounts: vec![AmountLimits {
+            denom: "usdc".to_string(),
+            min: Some(Uint128::new(100)),
+            max: None,
+        }],
+    };
+    let market_addr = setup_market(&mut app, Some(limits), None);
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(950, "usdc")],
+    }))
+    .unwrap();
+
+    app.execute_contract(
+        not_a_scammer,
+        market_addr.clone(),
+        &create_deal_msg(&app, Coin::new(900, "usdc"), false),
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    // 50 usdc are left after crossing, below the minimum amount of a deal.
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &create_deal_msg(&app, Coin::new(950, "astro"), true),
+        &[Coin::new(950, "usdc")],
+    )
+    .unwrap();
+
+    assert!(
+        deal_ids(&app, &market_addr).is_empty(),
+        "expected the leftover not to be stored as a deal"
+    );
+    assert_eq!(
+        app.wrap().query_balance(&stepit, "usdc").unwrap().amount,
+        Uint128::new(50),
+        "expected the leftover to be refunded"
+    );
+    assert_eq!(
+        app.wrap().query_balance(&stepit, "astro").unwrap().amount,
+        Uint128::new(980),
+        "expected creator to receive crossed astro less the fee"
+    );
+}
+
+#[test]
+fn match_crossing_skips_conditional_deals() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_market(&mut app, None, Some("0xoracle".to_string()));
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(2_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(500, "usdc")],
+    }))
+    .unwrap();
+
+    // Deals at 0.9 reserved to the taker, reserved to a group, and with a price trigger.
+    let conditional_deals = [
+        (vec![stepit.to_string()], None, None),
+        (vec![], Some("0xdesks".to_string()), None),
+        (vec![], None, Some(PriceTrigger::Above(Decimal::one()))),
+    ];
+    for (counterparties, group, trigger) in conditional_deals {
+        app.execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::CreateDeal {
+                coin_out: Coin::new(450, "usdc"),
+                counterparties,
+                group,
+                expiry: Expiration::AtHeight(app.block_info().height + 10),
+                match_crossing: None,
+                kind: None,
+                trigger,
+                coin_out_alternatives: vec![],
+            },
+            &[Coin::new(500, "astro")],
+        )
+        .unwrap();
+    }
+    app.execute_contract(
+        not_a_scammer,
+        market_addr.clone(),
+        &create_deal_msg(&app, Coin::new(500, "usdc"), false),
+        &[Coin::new(500, "astro")],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &create_deal_msg(&app, Coin::new(500, "astro"), true),
+        &[Coin::new(500, "usdc")],
+    )
+    .unwrap();
+
+    assert_eq!(
+        deal_ids(&app, &market_addr),
+        vec![0, 1, 2],
+        "expected only the open deal to be crossed"
+    );
+    assert_eq!(
+        app.wrap().query_balance(&stepit, "astro").unwrap().amount,
+        Uint128::new(490),
+        "expected creator to receive crossed astro less the fee"
+    );
+}
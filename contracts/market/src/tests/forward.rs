@@ -0,0 +1,330 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::{Expiration, Scheduled};
+
+use crate::{
+    error::ContractError,
+    msg::{AllDealsResponse, ExecuteMsg, QueryMsg},
+};
+
+use common::market::InstantiateMsg;
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+// Instantiates a market with a 2% fee and mints 1_000 astro to 0xtrustme and 1_000 usdc to
+// 0xstepit.
+fn setup_market(app: &mut App) -> Addr {
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(market_id, owner, &init_market_msg, &[], "otc-market", None)
+        .unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "usdc")],
+    }))
+    .unwrap();
+
+    market_addr
+}
+
+// Returns the message to create a forward of 1_000 astro for 1_000 usdc with a 10% collateral,
+// settled 10 blocks after the current one.
+fn create_forward_msg(app: &App, collateral: Decimal) -> ExecuteMsg {
+    ExecuteMsg::CreateForward {
+        coin_in: Coin::new(1_000, "astro"),
+        coin_out: Coin::new(1_000, "usdc"),
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 5),
+        settle_at: Scheduled::AtHeight(app.block_info().height + 10),
+        collateral,
+    }
+}
+
+#[test]
+fn forward_settle_works() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+    let keeper = Addr::unchecked("0xkeeper".to_string());
+
+    let market_addr = setup_market(&mut app);
+
+    let create_msg = create_forward_msg(&app, Decimal::percent(10));
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &create_msg,
+            &[Coin::new(1_000, "astro")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::WrongCoin {
+            denom: "astro".to_string(),
+            amount: Uint128::new(100)
+        },
+        "expected error because only the collateral must be deposited"
+    );
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &create_msg,
+        &[Coin::new(100, "astro")],
+    )
+    .unwrap();
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::AcceptForward {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+        },
+        &[Coin::new(100, "usdc")],
+    )
+    .unwrap();
+
+    let settle_msg = ExecuteMsg::SettleForward {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+    };
+    let err = app
+        .execute_contract(keeper.clone(), market_addr.clone(), &settle_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::SettlementNotReached {},
+        "expected error because settlement is not reached"
+    );
+
+    let fund_msg = ExecuteMsg::FundForward {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+    };
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &fund_msg,
+        &[Coin::new(900, "astro")],
+    )
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(100, "usdc")],
+    }))
+    .unwrap();
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &fund_msg,
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::WrongCoin {
+            denom: "usdc".to_string(),
+            amount: Uint128::new(900)
+        },
+        "expected error because funds exceed the remaining amount"
+    );
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &fund_msg,
+        &[Coin::new(900, "usdc")],
+    )
+    .unwrap();
+
+    app.update_block(|block| block.height += 10);
+
+    app.execute_contract(keeper, market_addr.clone(), &settle_msg, &[])
+        .unwrap();
+
+    assert_eq!(
+        app.wrap().query_balance(&stepit, "astro").unwrap().amount,
+        Uint128::new(980),
+        "expected astro sent to the counterparty less fee"
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "usdc")
+            .unwrap()
+            .amount,
+        Uint128::new(980),
+        "expected usdc sent to the creator less fee"
+    );
+    let resp: AllDealsResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr, &QueryMsg::AllDeals {})
+        .unwrap();
+    assert!(resp.deals.is_empty(), "expected settled forward removed");
+}
+
+#[test]
+fn forward_default_works() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_market(&mut app);
+
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &create_forward_msg(&app, Decimal::zero()),
+            &[Coin::new(100, "astro")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::InvalidCollateral {},
+        "expected error because collateral is zero"
+    );
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &create_forward_msg(&app, Decimal::percent(10)),
+        &[Coin::new(100, "astro")],
+    )
+    .unwrap();
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::AcceptForward {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+        },
+        &[Coin::new(100, "usdc")],
+    )
+    .unwrap();
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::FundForward {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+        },
+        &[Coin::new(900, "astro")],
+    )
+    .unwrap();
+
+    app.update_block(|block| block.height += 10);
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr,
+        &ExecuteMsg::SettleForward {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+        },
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "astro")
+            .unwrap()
+            .amount,
+        Uint128::new(1_000),
+        "expected creator deposit refunded"
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "usdc")
+            .unwrap()
+            .amount,
+        Uint128::new(100),
+        "expected defaulted collateral sent to the creator without fee"
+    );
+    assert_eq!(
+        app.wrap().query_balance(&stepit, "usdc").unwrap().amount,
+        Uint128::new(900),
+        "expected counterparty to lose the collateral"
+    );
+}
+
+#[test]
+fn forward_accept_after_settlement_fails() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_market(&mut app);
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::CreateForward {
+            coin_in: Coin::new(1_000, "astro"),
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::Never {},
+            settle_at: Scheduled::AtHeight(app.block_info().height + 10),
+            collateral: Decimal::percent(10),
+        },
+        &[Coin::new(100, "astro")],
+    )
+    .unwrap();
+
+    app.update_block(|block| block.height += 10);
+
+    let err = app
+        .execute_contract(
+            stepit,
+            market_addr,
+            &ExecuteMsg::AcceptForward {
+                creator: not_a_scammer.to_string(),
+                deal_id: 0,
+            },
+            &[Coin::new(100, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::SettlementReached {},
+        "expected error because the settlement is reached"
+    );
+}
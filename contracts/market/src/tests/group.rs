@@ -0,0 +1,323 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
+use cw4::Member;
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
+
+use crate::{
+    error::ContractError,
+    msg::{DealsByCreatorResponse, ExecuteMsg, OrderBookResponse, QueryMsg},
+};
+
+use common::market::InstantiateMsg;
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+// Creates a cw4 group contract.
+pub fn group_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw4_group::contract::execute,
+        cw4_group::contract::instantiate,
+        cw4_group::contract::query,
+    );
+    Box::new(contract)
+}
+
+// Instantiates a cw4 group with 0xtrustme and 0xstepit as members, and a market gated by the group
+// if required.
+fn setup_market(app: &mut App, market_group: bool) -> (Addr, Addr) {
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+    let outsider = Addr::unchecked("0xoutsider".to_string());
+
+    let group_id = app.store_code(group_contract());
+    let group_addr = app
+        .instantiate_contract(
+            group_id,
+            owner.clone(),
+            &cw4_group::msg::InstantiateMsg {
+                admin: None,
+                members: vec![
+                    Member {
+                        addr: not_a_scammer.to_string(),
+                        weight: 1,
+                    },
+                    Member {
+                        addr: stepit.to_string(),
+                        weight: 1,
+                    },
+                ],
+            },
+            &[],
+            "otc-group",
+            None,
+        )
+        .unwrap();
+
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: market_group.then(|| group_addr.to_string()),
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(market_id, owner, &init_market_msg, &[], "otc-market", None)
+        .unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(3_000, "astro")],
+    }))
+    .unwrap();
+    for addr in [&stepit, &outsider] {
+        app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: addr.to_string(),
+            amount: vec![Coin::new(3_000, "astro"), Coin::new(3_000, "usdc")],
+        }))
+        .unwrap();
+    }
+
+    (market_addr, group_addr)
+}
+
+#[test]
+fn market_group_works() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+    let outsider = Addr::unchecked("0xoutsider".to_string());
+
+    let (market_addr, _) = setup_market(&mut app, true);
+
+    let create_deal_msg = ExecuteMsg::CreateDeal {
+        coin_out: Coin::new(1_000, "usdc"),
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
+    };
+    let err = app
+        .execute_contract(
+            outsider.clone(),
+            market_addr.clone(),
+            &create_deal_msg,
+            &[Coin::new(1_000, "astro")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::NotGroupMember {
+            addr: outsider.to_string()
+        },
+        "expected error because creator is not a member"
+    );
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &create_deal_msg,
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    let accept_deal_msg = ExecuteMsg::AcceptDeal {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+        expected_coin_in: None,
+        min_receive: None,
+        expected_version: None,
+    };
+    let err = app
+        .execute_contract(
+            outsider.clone(),
+            market_addr.clone(),
+            &accept_deal_msg,
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::NotGroupMember {
+            addr: outsider.to_string()
+        },
+        "expected error because counterparty is not a member"
+    );
+
+    let err = app
+        .execute_contract(
+            outsider.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::MarketTake {
+                max_price: Decimal::one(),
+                min_receive: Uint128::zero(),
+                denom_out: None,
+            },
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::NotGroupMember {
+            addr: outsider.to_string()
+        },
+        "expected error because taker is not a member"
+    );
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &accept_deal_msg,
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+
+    let resp: DealsByCreatorResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr,
+            &QueryMsg::DealsByCreator {
+                creator: not_a_scammer.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp.deals[0].1.counterparty,
+        Some(stepit),
+        "expected the member to be the counterparty"
+    );
+}
+
+#[test]
+fn deal_group_works() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+    let outsider = Addr::unchecked("0xoutsider".to_string());
+
+    let (market_addr, group_addr) = setup_market(&mut app, false);
+
+    // A deal cannot be reserved both to counterparties and a group
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::CreateDeal {
+                coin_out: Coin::new(1_000, "usdc"),
+                counterparties: vec![stepit.to_string()],
+                group: Some(group_addr.to_string()),
+                expiry: Expiration::AtHeight(app.block_info().height + 10),
+                match_crossing: None,
+                kind: None,
+                trigger: None,
+                coin_out_alternatives: vec![],
+            },
+            &[Coin::new(1_000, "astro")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::CounterpartiesAndGroup {},
+        "expected error because both counterparties and group are specified"
+    );
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: Some(group_addr.to_string()),
+            expiry: Expiration::AtHeight(app.block_info().height + 10),
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    let resp: OrderBookResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr.clone(),
+            &QueryMsg::OrderBook {
+                depth: None,
+                first_denom: None,
+                second_denom: None,
+            },
+        )
+        .unwrap();
+    assert!(
+        resp.sell_first_coin.is_empty(),
+        "expected group deal not to be part of the book"
+    );
+
+    let accept_deal_msg = ExecuteMsg::AcceptDeal {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+        expected_coin_in: None,
+        min_receive: None,
+        expected_version: None,
+    };
+    let err = app
+        .execute_contract(
+            outsider.clone(),
+            market_addr.clone(),
+            &accept_deal_msg,
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::NotGroupMember {
+            addr: outsider.to_string()
+        },
+        "expected error because counterparty is not a member"
+    );
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &accept_deal_msg,
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+
+    let resp: DealsByCreatorResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr,
+            &QueryMsg::DealsByCreator {
+                creator: not_a_scammer.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp.deals[0].1.counterparty,
+        Some(stepit),
+        "expected the member to be the counterparty"
+    );
+}
@@ -0,0 +1,239 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_ownable::OwnershipError;
+use cw_utils::{Duration, Expiration};
+
+use crate::{
+    error::ContractError,
+    msg::{ExecuteMsg, QueryMsg},
+};
+
+use common::market::{AmountLimits, Config, DealLimits, InstantiateMsg};
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+// Deals must last between 10 and 100 blocks and offer or request between 100 and 10_000 astro.
+fn market_limits() -> DealLimits {
+    DealLimits {
+        min_duration: Some(Duration::Height(10)),
+        max_duration: Some(Duration::Height(100)),
+        amounts: vec![AmountLimits {
+            denom: "astro".to_string(),
+            min: Some(Uint128::new(100)),
+            max: Some(Uint128::new(10_000)),
+        }],
+    }
+}
+
+fn setup_market(app: &mut App) -> Addr {
+    let owner = Addr::unchecked(OWNER);
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: Some(market_limits()),
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(market_id, owner, &init_market_msg, &[], "otc-market", None)
+        .unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(100_000, "astro")],
+    }))
+    .unwrap();
+
+    market_addr
+}
+
+#[test]
+fn limits_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_market(&mut app);
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::AtHeight(app.block_info().height + 100),
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(10_000, "astro")],
+    )
+    .unwrap();
+
+    // Remove the maximum amount
+    let mut new_limits = market_limits();
+    new_limits.amounts[0].max = None;
+    app.execute_contract(
+        owner,
+        market_addr.clone(),
+        &ExecuteMsg::UpdateLimits {
+            limits: new_limits.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let config: Config = app
+        .wrap()
+        .query_wasm_smart(market_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.limits, new_limits, "expected limits to be updated");
+
+    app.execute_contract(
+        not_a_scammer,
+        market_addr,
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::AtHeight(app.block_info().height + 10),
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(20_000, "astro")],
+    )
+    .unwrap();
+}
+
+#[test]
+fn limits_handle_errors() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_market(&mut app);
+
+    let height = app.block_info().height;
+    let time = app.block_info().time;
+    let cases = [
+        (
+            Coin::new(99, "astro"),
+            Expiration::AtHeight(height + 10),
+            ContractError::AmountBelowMinimum {
+                denom: "astro".to_string(),
+                min: Uint128::new(100),
+            },
+        ),
+        (
+            Coin::new(10_001, "astro"),
+            Expiration::AtHeight(height + 10),
+            ContractError::AmountAboveMaximum {
+                denom: "astro".to_string(),
+                max: Uint128::new(10_000),
+            },
+        ),
+        (
+            Coin::new(1_000, "astro"),
+            Expiration::AtHeight(height + 9),
+            ContractError::DurationBelowMinimum {},
+        ),
+        (
+            Coin::new(1_000, "astro"),
+            Expiration::AtHeight(height + 101),
+            ContractError::DurationAboveMaximum {},
+        ),
+        (
+            Coin::new(1_000, "astro"),
+            Expiration::Never {},
+            ContractError::DurationAboveMaximum {},
+        ),
+        (
+            Coin::new(1_000, "astro"),
+            Expiration::AtTime(time.plus_seconds(100)),
+            ContractError::ExpiryUnitMismatch {},
+        ),
+    ];
+    for (coin_in, expiry, expected_err) in cases {
+        let err = app
+            .execute_contract(
+                not_a_scammer.clone(),
+                market_addr.clone(),
+                &ExecuteMsg::CreateDeal {
+                    coin_out: Coin::new(1_000, "usdc"),
+                    counterparties: vec![],
+                    group: None,
+                    expiry,
+                    match_crossing: None,
+                    kind: None,
+                    trigger: None,
+                    coin_out_alternatives: vec![],
+                },
+                &[coin_in],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ContractError>().unwrap(),
+            &expected_err,
+            "expected deal outside of the market limits to fail"
+        );
+    }
+
+    // Only the owner can update the limits
+    let err = app
+        .execute_contract(
+            not_a_scammer,
+            market_addr.clone(),
+            &ExecuteMsg::UpdateLimits {
+                limits: DealLimits::default(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::Ownership(OwnershipError::NotOwner),
+        "expected error because not the owner"
+    );
+
+    let mut invalid_limits = market_limits();
+    invalid_limits.max_duration = Some(Duration::Time(1_000));
+    let err = app
+        .execute_contract(
+            owner,
+            market_addr,
+            &ExecuteMsg::UpdateLimits {
+                limits: invalid_limits,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::InvalidLimits {},
+        "expected error because durations use different units"
+    );
+}
@@ -0,0 +1,263 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
+
+use crate::{
+    error::ContractError,
+    msg::{AllDealsResponse, ExecuteMsg, QueryMsg},
+};
+
+use common::market::InstantiateMsg;
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+// Instantiates a market and creates deals selling astro at 1.0, 1.2, and 1.1 usdc from
+// 0xtrustme plus a cheaper one from the taker 0xstepit.
+fn setup_market(app: &mut App) -> Addr {
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(
+            market_id,
+            owner.clone(),
+            &init_market_msg,
+            &[],
+            "otc-market",
+            None,
+        )
+        .unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(3_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(100, "astro"), Coin::new(10_000, "usdc")],
+    }))
+    .unwrap();
+
+    for usdc_amount in [1_000, 1_200, 1_100] {
+        app.execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::CreateDeal {
+                coin_out: Coin::new(usdc_amount, "usdc"),
+                counterparties: vec![],
+                group: None,
+                expiry: Expiration::AtHeight(app.block_info().height + 10),
+                match_crossing: None,
+                kind: None,
+                trigger: None,
+                coin_out_alternatives: vec![],
+            },
+            &[Coin::new(1_000, "astro")],
+        )
+        .unwrap();
+    }
+    app.execute_contract(
+        stepit,
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(50, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::AtHeight(app.block_info().height + 10),
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(100, "astro")],
+    )
+    .unwrap();
+
+    market_addr
+}
+
+#[test]
+fn market_take_works() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_market(&mut app);
+
+    // Fill the deal at 1.0 and half of the deal at 1.1, skipping the taker own deal
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::MarketTake {
+            max_price: Decimal::percent(110),
+            min_receive: Uint128::new(1_470),
+            denom_out: None,
+        },
+        &[Coin::new(1_550, "usdc")],
+    )
+    .unwrap();
+
+    let resp: AllDealsResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr.clone(), &QueryMsg::AllDeals {})
+        .unwrap();
+    assert_eq!(resp.deals.len(), 3, "expected filled deal to be removed");
+    let partial_deal = &resp.deals[2].1;
+    assert_eq!(
+        (partial_deal.coin_in.clone(), partial_deal.coin_out.clone()),
+        (Coin::new(500, "astro"), Coin::new(550, "usdc")),
+        "expected deal to keep the remaining amounts"
+    );
+
+    // Fill the remaining of the deal at 1.1 and refund what is left
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::MarketTake {
+            max_price: Decimal::percent(110),
+            min_receive: Uint128::zero(),
+            denom_out: None,
+        },
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+
+    let resp: AllDealsResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr, &QueryMsg::AllDeals {})
+        .unwrap();
+    assert_eq!(
+        resp.deals
+            .iter()
+            .map(|(key, _)| key.1)
+            .collect::<Vec<u64>>(),
+        vec![3, 1],
+        "expected only the deal above max price and the taker deal to remain"
+    );
+
+    let taker_astro = app.wrap().query_balance(stepit.clone(), "astro").unwrap();
+    assert_eq!(
+        taker_astro.amount,
+        Uint128::new(1_960),
+        "expected taker to receive astro less the fee"
+    );
+    let taker_usdc = app.wrap().query_balance(stepit, "usdc").unwrap();
+    assert_eq!(
+        taker_usdc.amount,
+        Uint128::new(7_900),
+        "expected taker to be refunded of the amount not spent"
+    );
+    let creator_usdc = app.wrap().query_balance(not_a_scammer, "usdc").unwrap();
+    assert_eq!(
+        creator_usdc.amount,
+        Uint128::new(2_058),
+        "expected creator to receive usdc less the fee"
+    );
+    let owner_usdc = app.wrap().query_balance(OWNER, "usdc").unwrap();
+    let owner_astro = app.wrap().query_balance(OWNER, "astro").unwrap();
+    assert_eq!(
+        (owner_usdc.amount, owner_astro.amount),
+        (Uint128::new(42), Uint128::new(40)),
+        "expected owner to receive the fee from both sides"
+    );
+}
+
+#[test]
+fn market_take_handle_errors() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+
+    let market_addr = setup_market(&mut app);
+
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::MarketTake {
+                max_price: Decimal::one(),
+                min_receive: Uint128::new(990),
+                denom_out: None,
+            },
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::MinReceiveNotMet {
+            received: Uint128::new(980),
+            min_receive: Uint128::new(990),
+        },
+        "expected error because received amount less the fee is too low"
+    );
+
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::MarketTake {
+                max_price: Decimal::percent(90),
+                min_receive: Uint128::one(),
+                denom_out: None,
+            },
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::MinReceiveNotMet {
+            received: Uint128::zero(),
+            min_receive: Uint128::one(),
+        },
+        "expected error because no deal below max price"
+    );
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "uluna")],
+    }))
+    .unwrap();
+    let err = app
+        .execute_contract(
+            stepit,
+            market_addr,
+            &ExecuteMsg::MarketTake {
+                max_price: Decimal::one(),
+                min_receive: Uint128::zero(),
+                denom_out: None,
+            },
+            &[Coin::new(1_000, "uluna")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::CoinNotAllowed {},
+        "expected error because coin not allowed in the market"
+    );
+}
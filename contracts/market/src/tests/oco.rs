@@ -0,0 +1,252 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
+
+use crate::{
+    error::ContractError,
+    msg::{ExecuteMsg, OcoGroupResponse, QueryMsg},
+};
+
+use common::market::{DealStatus, InstantiateMsg};
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+// Instantiates a market with a 2% fee and creates three deals from 0xtrustme, each one selling
+// 1_000 astro for 1_000, 1_100, and 1_200 usdc.
+fn setup_deals(app: &mut App) -> Addr {
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(market_id, owner, &init_market_msg, &[], "otc-market", None)
+        .unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(3_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(2_000, "usdc")],
+    }))
+    .unwrap();
+
+    for amount in [1_000, 1_100, 1_200] {
+        app.execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::CreateDeal {
+                coin_out: Coin::new(amount, "usdc"),
+                counterparties: vec![],
+                group: None,
+                expiry: Expiration::Never {},
+                match_crossing: None,
+                kind: None,
+                trigger: None,
+                coin_out_alternatives: vec![],
+            },
+            &[Coin::new(1_000, "astro")],
+        )
+        .unwrap();
+    }
+
+    market_addr
+}
+
+#[test]
+fn oco_accept_cancels_group_works() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_deals(&mut app);
+
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::LinkOco {
+                creator: not_a_scammer.to_string(),
+                deal_ids: vec![0],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::InvalidOcoGroup {},
+        "expected error because a group requires two deals"
+    );
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::LinkOco {
+            creator: not_a_scammer.to_string(),
+            deal_ids: vec![0, 1],
+        },
+        &[],
+    )
+    .unwrap();
+
+    let resp: OcoGroupResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr.clone(), &QueryMsg::OcoGroup { group_id: 1 })
+        .unwrap();
+    assert_eq!(resp.deals.len(), 2, "expected two deals in the group");
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::AcceptDeal {
+            creator: not_a_scammer.to_string(),
+            deal_id: 1,
+            expected_coin_in: None,
+            min_receive: None,
+            expected_version: None,
+        },
+        &[Coin::new(1_100, "usdc")],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "astro")
+            .unwrap()
+            .amount,
+        Uint128::new(1_000),
+        "expected the other deal of the group refunded"
+    );
+    let resp: OcoGroupResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr, &QueryMsg::OcoGroup { group_id: 1 })
+        .unwrap();
+    assert_eq!(resp.deals.len(), 1, "expected the other deal cancelled");
+    assert_eq!(resp.deals[0].0 .1, 1);
+    assert_eq!(resp.deals[0].1.status, DealStatus::matched_no_withdraw());
+}
+
+#[test]
+fn oco_market_take_works() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_deals(&mut app);
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::LinkOco {
+            creator: not_a_scammer.to_string(),
+            deal_ids: vec![0, 1],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // The fill of the cheapest deal cancels the other one of the group, so the taker continues
+    // with the deal not grouped.
+    app.execute_contract(
+        stepit.clone(),
+        market_addr,
+        &ExecuteMsg::MarketTake {
+            max_price: Decimal::percent(110),
+            min_receive: Uint128::zero(),
+            denom_out: None,
+        },
+        &[Coin::new(2_000, "usdc")],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.wrap().query_balance(&stepit, "astro").unwrap().amount,
+        Uint128::new(980),
+        "expected only the cheapest deal of the group filled"
+    );
+    assert_eq!(
+        app.wrap().query_balance(&stepit, "usdc").unwrap().amount,
+        Uint128::new(1_000),
+        "expected the coin not spent refunded"
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "astro")
+            .unwrap()
+            .amount,
+        Uint128::new(1_000),
+        "expected the other deal of the group refunded"
+    );
+}
+
+#[test]
+fn oco_market_take_continues_after_cancellation() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_deals(&mut app);
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::LinkOco {
+            creator: not_a_scammer.to_string(),
+            deal_ids: vec![0, 1],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // The deal cancelled by the fill of the cheapest one is replaced by the next deal of the book.
+    app.execute_contract(
+        stepit.clone(),
+        market_addr,
+        &ExecuteMsg::MarketTake {
+            max_price: Decimal::percent(120),
+            min_receive: Uint128::zero(),
+            denom_out: None,
+        },
+        &[Coin::new(2_000, "usdc")],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.wrap().query_balance(&stepit, "usdc").unwrap().amount,
+        Uint128::zero(),
+        "expected the whole coin spent"
+    );
+    assert_eq!(
+        app.wrap().query_balance(&stepit, "astro").unwrap().amount,
+        Uint128::new(1_797),
+        "expected the cheapest deal and part of the next one filled"
+    );
+}
@@ -0,0 +1,286 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
+
+use crate::{
+    error::ContractError,
+    msg::{AllDealsResponse, ExecuteMsg, QueryMsg},
+};
+
+use common::market::{DealKind, DealStatus, InstantiateMsg};
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+// Instantiates a market with a 2% fee and creates a call option from 0xtrustme on 1_000 astro
+// with a strike of 1_000 usdc and a premium of 100 usdc.
+fn setup_option(app: &mut App) -> Addr {
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(market_id, owner, &init_market_msg, &[], "otc-market", None)
+        .unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_100, "usdc")],
+    }))
+    .unwrap();
+
+    app.execute_contract(
+        not_a_scammer,
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::AtHeight(app.block_info().height + 10),
+            match_crossing: None,
+            kind: Some(DealKind::Option {
+                premium: Coin::new(100, "usdc"),
+            }),
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    market_addr
+}
+
+#[test]
+fn option_exercise_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_option(&mut app);
+
+    // Options cannot be accepted as spot deals
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::AcceptDeal {
+                creator: not_a_scammer.to_string(),
+                deal_id: 0,
+                expected_coin_in: None,
+                min_receive: None,
+                expected_version: None,
+            },
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::WrongDealKind {},
+        "expected error because the deal is an option"
+    );
+
+    let buy_option_msg = ExecuteMsg::BuyOption {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+    };
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &buy_option_msg,
+            &[Coin::new(90, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::WrongCoin {
+            denom: "usdc".to_string(),
+            amount: Uint128::new(100)
+        },
+        "expected error because premium is wrong"
+    );
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &buy_option_msg,
+        &[Coin::new(100, "usdc")],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "usdc")
+            .unwrap()
+            .amount,
+        Uint128::new(98),
+        "expected premium paid to the creator less fee"
+    );
+    let resp: AllDealsResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr.clone(), &QueryMsg::AllDeals {})
+        .unwrap();
+    assert_eq!(resp.deals[0].1.status, DealStatus::OptionBought);
+
+    // Only the buyer can exercise
+    let exercise_option_msg = ExecuteMsg::ExerciseOption {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+    };
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: owner.to_string(),
+        amount: vec![Coin::new(1_000, "usdc")],
+    }))
+    .unwrap();
+    let err = app
+        .execute_contract(
+            owner,
+            market_addr.clone(),
+            &exercise_option_msg,
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::Unauthorized {},
+        "expected error because sender is not the buyer"
+    );
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &exercise_option_msg,
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.wrap().query_balance(&stepit, "astro").unwrap().amount,
+        Uint128::new(980),
+        "expected underlying sent to the buyer less fee"
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "usdc")
+            .unwrap()
+            .amount,
+        Uint128::new(1_078),
+        "expected strike sent to the creator less fee"
+    );
+    let resp: AllDealsResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr, &QueryMsg::AllDeals {})
+        .unwrap();
+    assert!(resp.deals.is_empty(), "expected exercised option removed");
+}
+
+#[test]
+fn option_expiry_works() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_option(&mut app);
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::BuyOption {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+        },
+        &[Coin::new(100, "usdc")],
+    )
+    .unwrap();
+
+    // The creator cannot reclaim the underlying before expiry
+    let withdraw_msg = ExecuteMsg::Withdraw {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+    };
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &withdraw_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::Unauthorized {},
+        "expected error because the option is not expired"
+    );
+
+    app.update_block(|block| block.height += 10);
+
+    let err = app
+        .execute_contract(
+            stepit,
+            market_addr.clone(),
+            &ExecuteMsg::ExerciseOption {
+                creator: not_a_scammer.to_string(),
+                deal_id: 0,
+            },
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::DealNotAvailable {},
+        "expected error because the option is expired"
+    );
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &withdraw_msg,
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "astro")
+            .unwrap()
+            .amount,
+        Uint128::new(1_000),
+        "expected underlying reclaimed without fee"
+    );
+    let resp: AllDealsResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr, &QueryMsg::AllDeals {})
+        .unwrap();
+    assert!(resp.deals.is_empty(), "expected expired option removed");
+}
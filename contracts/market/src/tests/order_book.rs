@@ -0,0 +1,237 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
+
+use crate::msg::{ExecuteMsg, OrderBookLevel, OrderBookResponse, QueryMsg};
+
+use common::market::InstantiateMsg;
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+#[test]
+fn order_book_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    // Store and instantiate the market contract.
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(
+            market_id,
+            owner.clone(),
+            &init_market_msg,
+            &[],
+            "otc-market",
+            None,
+        )
+        .unwrap();
+
+    // Mint tokens to the two creators
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(10_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(10_000, "usdc")],
+    }))
+    .unwrap();
+
+    let mut create_deal =
+        |creator: &Addr, coin_in: Coin, coin_out: Coin, reserved: bool, timeout| {
+            app.execute_contract(
+                creator.clone(),
+                market_addr.clone(),
+                &ExecuteMsg::CreateDeal {
+                    coin_out,
+                    counterparties: reserved.then(|| OWNER.to_string()).into_iter().collect(),
+                    group: None,
+                    expiry: Expiration::AtHeight(app.block_info().height + timeout),
+                    match_crossing: None,
+                    kind: None,
+                    trigger: None,
+                    coin_out_alternatives: vec![],
+                },
+                &[coin_in],
+            )
+            .unwrap();
+        };
+
+    // Deals selling astro. The one expiring soon and the reserved one are not part of the book.
+    create_deal(
+        &not_a_scammer,
+        Coin::new(1_000, "astro"),
+        Coin::new(500, "usdc"),
+        false,
+        1,
+    );
+    create_deal(
+        &not_a_scammer,
+        Coin::new(1_000, "astro"),
+        Coin::new(1_200, "usdc"),
+        false,
+        10,
+    );
+    create_deal(
+        &not_a_scammer,
+        Coin::new(1_000, "astro"),
+        Coin::new(1_000, "usdc"),
+        false,
+        10,
+    );
+    create_deal(
+        &not_a_scammer,
+        Coin::new(500, "astro"),
+        Coin::new(500, "usdc"),
+        false,
+        10,
+    );
+    create_deal(
+        &not_a_scammer,
+        Coin::new(1_000, "astro"),
+        Coin::new(100, "usdc"),
+        true,
+        10,
+    );
+    create_deal(
+        &not_a_scammer,
+        Coin::new(1_000, "astro"),
+        Coin::new(1_100, "usdc"),
+        false,
+        10,
+    );
+
+    // Deals selling usdc.
+    create_deal(
+        &stepit,
+        Coin::new(800, "usdc"),
+        Coin::new(1_000, "astro"),
+        false,
+        10,
+    );
+    create_deal(
+        &stepit,
+        Coin::new(900, "usdc"),
+        Coin::new(1_000, "astro"),
+        false,
+        10,
+    );
+
+    // A matched deal leaves the book.
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::AcceptDeal {
+            creator: not_a_scammer.to_string(),
+            deal_id: 5,
+            expected_coin_in: None,
+            min_receive: None,
+            expected_version: None,
+        },
+        &[Coin::new(1_100, "usdc")],
+    )
+    .unwrap();
+
+    app.update_block(|block| block.height += 2);
+
+    let resp: OrderBookResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr.clone(),
+            &QueryMsg::OrderBook {
+                depth: None,
+                first_denom: None,
+                second_denom: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        resp.sell_first_coin,
+        vec![
+            OrderBookLevel {
+                price: Decimal::one(),
+                offered: Uint128::new(1_500),
+                requested: Uint128::new(1_500),
+                deals: 2,
+            },
+            OrderBookLevel {
+                price: Decimal::percent(120),
+                offered: Uint128::new(1_000),
+                requested: Uint128::new(1_200),
+                deals: 1,
+            },
+        ],
+        "expected astro levels sorted by lowest price first"
+    );
+    assert_eq!(
+        resp.sell_second_coin,
+        vec![
+            OrderBookLevel {
+                price: Decimal::percent(90),
+                offered: Uint128::new(900),
+                requested: Uint128::new(1_000),
+                deals: 1,
+            },
+            OrderBookLevel {
+                price: Decimal::percent(80),
+                offered: Uint128::new(800),
+                requested: Uint128::new(1_000),
+                deals: 1,
+            },
+        ],
+        "expected usdc levels sorted by highest price first"
+    );
+
+    // Depth limits the levels of each side.
+    let resp: OrderBookResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr,
+            &QueryMsg::OrderBook {
+                depth: Some(1),
+                first_denom: None,
+                second_denom: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        resp.sell_first_coin.len(),
+        1,
+        "expected only the best level"
+    );
+    assert_eq!(resp.sell_first_coin[0].price, Decimal::one());
+    assert_eq!(
+        resp.sell_second_coin.len(),
+        1,
+        "expected only the best level"
+    );
+    assert_eq!(resp.sell_second_coin[0].price, Decimal::percent(90));
+}
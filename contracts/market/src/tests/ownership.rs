@@ -0,0 +1,200 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_ownable::{Action, Ownership};
+use cw_utils::Expiration;
+
+use crate::msg::{ExecuteMsg, QueryMsg};
+
+use common::market::InstantiateMsg;
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+// Instantiates a market with a 2% fee.
+fn setup_market(app: &mut App) -> Addr {
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    app.instantiate_contract(
+        market_id,
+        Addr::unchecked(OWNER),
+        &init_market_msg,
+        &[],
+        "otc-market",
+        None,
+    )
+    .unwrap()
+}
+
+// Exchanges 1_000 astro of 0xtrustme for 1_000 usdc of 0xstepit and settles the deal.
+fn exchange(app: &mut App, market_addr: &Addr, deal_id: u64) {
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "usdc")],
+    }))
+    .unwrap();
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::Never {},
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::AcceptDeal {
+            creator: not_a_scammer.to_string(),
+            deal_id,
+            expected_coin_in: None,
+            min_receive: None,
+            expected_version: None,
+        },
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+    app.execute_contract(
+        stepit,
+        market_addr.clone(),
+        &ExecuteMsg::Settle {
+            creator: not_a_scammer.to_string(),
+            deal_id,
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn fees_follow_ownership_transfer() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let new_owner = Addr::unchecked("0xnewowner".to_string());
+
+    let market_addr = setup_market(&mut app);
+
+    app.execute_contract(
+        owner.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::UpdateOwnership(Action::TransferOwnership {
+            new_owner: new_owner.to_string(),
+            expiry: None,
+        }),
+        &[],
+    )
+    .unwrap();
+
+    // Until the transfer is accepted fees are sent to the current owner.
+    exchange(&mut app, &market_addr, 0);
+    assert_eq!(
+        app.wrap().query_balance(&owner, "usdc").unwrap().amount,
+        Uint128::new(20),
+        "expected fee sent to the current owner"
+    );
+
+    app.execute_contract(
+        new_owner.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::UpdateOwnership(Action::AcceptOwnership),
+        &[],
+    )
+    .unwrap();
+
+    exchange(&mut app, &market_addr, 1);
+    assert_eq!(
+        app.wrap().query_balance(&owner, "usdc").unwrap().amount,
+        Uint128::new(20),
+        "expected no more fee sent to the previous owner"
+    );
+    assert_eq!(
+        app.wrap().query_balance(&new_owner, "usdc").unwrap().amount,
+        Uint128::new(20),
+        "expected fee sent to the new owner"
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(&new_owner, "astro")
+            .unwrap()
+            .amount,
+        Uint128::new(20)
+    );
+}
+
+#[test]
+fn no_fees_after_ownership_renounce() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_market(&mut app);
+
+    app.execute_contract(
+        owner.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::UpdateOwnership(Action::RenounceOwnership),
+        &[],
+    )
+    .unwrap();
+
+    let ownership: Ownership<Addr> = app
+        .wrap()
+        .query_wasm_smart(market_addr.clone(), &QueryMsg::Ownership {})
+        .unwrap();
+    assert_eq!(ownership.owner, None, "expected ownership renounced");
+
+    exchange(&mut app, &market_addr, 0);
+    assert_eq!(
+        app.wrap().query_balance(&stepit, "astro").unwrap().amount,
+        Uint128::new(1_000),
+        "expected no fee deducted without owner"
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "usdc")
+            .unwrap()
+            .amount,
+        Uint128::new(1_000),
+        "expected no fee deducted without owner"
+    );
+    assert!(app.wrap().query_all_balances(&owner).unwrap().is_empty());
+}
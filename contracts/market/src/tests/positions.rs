@@ -0,0 +1,232 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
+use cw721::{NumTokensResponse, OwnerOfResponse};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
+
+use crate::{error::ContractError, msg::ExecuteMsg, msg::QueryMsg};
+
+use common::market::{Config, InstantiateMsg, Side};
+use positions::msg::{ExecuteMsg as PositionsExecuteMsg, QueryMsg as PositionsQueryMsg};
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    )
+    .with_reply(crate::contract::reply);
+    Box::new(contract)
+}
+
+// Creates a positions contract.
+pub fn positions_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        positions::contract::execute,
+        positions::contract::instantiate,
+        positions::contract::query,
+    );
+    Box::new(contract)
+}
+
+#[test]
+fn tokenized_positions_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let stepit_cold = Addr::unchecked("0xstepitcold".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+    let buyer = Addr::unchecked("0xbuyer".to_string());
+
+    // Store and instantiate the market contract.
+    let positions_id = app.store_code(positions_contract());
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(0),
+        positions_code_id: Some(positions_id),
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(
+            market_id,
+            owner.clone(),
+            &init_market_msg,
+            &[],
+            "otc-market",
+            None,
+        )
+        .unwrap();
+
+    let config: Config = app
+        .wrap()
+        .query_wasm_smart(market_addr.clone(), &QueryMsg::Config {})
+        .unwrap();
+    let positions_addr = config.positions.unwrap();
+
+    // Mint tokens to creator and counterparty
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "usdc")],
+    }))
+    .unwrap();
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::AtHeight(app.block_info().height + 10),
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::AcceptDeal {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+            expected_coin_in: None,
+            min_receive: None,
+            expected_version: None,
+        },
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+
+    let resp: OwnerOfResponse = app
+        .wrap()
+        .query_wasm_smart(
+            positions_addr.clone(),
+            &PositionsQueryMsg::OwnerOf {
+                token_id: "0-counterparty".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp.owner,
+        stepit.to_string(),
+        "expected counterparty position minted to the acceptor"
+    );
+
+    // Positions are moved through the positions contract
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::TransferPosition {
+                creator: not_a_scammer.to_string(),
+                deal_id: 0,
+                side: Side::Creator,
+                recipient: buyer.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::PositionsTokenized {},
+        "expected error because positions are tokenized"
+    );
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        positions_addr.clone(),
+        &PositionsExecuteMsg::TransferNft {
+            recipient: buyer.to_string(),
+            token_id: "0-creator".to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        stepit.clone(),
+        positions_addr.clone(),
+        &PositionsExecuteMsg::TransferNft {
+            recipient: stepit_cold.to_string(),
+            token_id: "0-counterparty".to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let withdraw_msg = ExecuteMsg::Withdraw {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+    };
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &withdraw_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::Unauthorized {},
+        "expected error because creator sold the position"
+    );
+
+    app.execute_contract(buyer.clone(), market_addr.clone(), &withdraw_msg, &[])
+        .unwrap();
+
+    let resp: NumTokensResponse = app
+        .wrap()
+        .query_wasm_smart(positions_addr.clone(), &PositionsQueryMsg::NumTokens {})
+        .unwrap();
+    assert_eq!(resp.count, 1, "expected withdrawn position to be burned");
+
+    // Settle pays the current owner of the counterparty position
+    app.execute_contract(
+        owner.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::Settle {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let buyer_balance = app.wrap().query_balance(buyer, "usdc").unwrap();
+    assert_eq!(
+        buyer_balance.amount,
+        Uint128::new(1_000),
+        "expected position buyer to receive usdc"
+    );
+    let counterparty_balance = app.wrap().query_balance(stepit_cold, "astro").unwrap();
+    assert_eq!(
+        counterparty_balance.amount,
+        Uint128::new(1_000),
+        "expected counterparty position owner to receive astro"
+    );
+
+    let resp: NumTokensResponse = app
+        .wrap()
+        .query_wasm_smart(positions_addr, &PositionsQueryMsg::NumTokens {})
+        .unwrap();
+    assert_eq!(resp.count, 0, "expected all positions to be burned");
+}
@@ -0,0 +1,252 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::{Duration, Expiration};
+
+use crate::{
+    error::ContractError,
+    msg::{AllDealsResponse, ExecuteMsg, QueryMsg},
+};
+
+use common::market::{DealKind, InstantiateMsg};
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+// Instantiates a market with a 2% fee and creates a recurring deal from 0xtrustme selling 3_000
+// astro in tranches of 1_000 astro for 500 usdc every 10 blocks.
+fn setup_recurring(app: &mut App, rollover: bool) -> Addr {
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(market_id, owner, &init_market_msg, &[], "otc-market", None)
+        .unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(3_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(2_000, "usdc")],
+    }))
+    .unwrap();
+
+    app.execute_contract(
+        not_a_scammer,
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(500, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::Never {},
+            match_crossing: None,
+            kind: Some(DealKind::Recurring {
+                interval: Duration::Height(10),
+                tranche: Uint128::new(1_000),
+                rollover,
+            }),
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(3_000, "astro")],
+    )
+    .unwrap();
+
+    market_addr
+}
+
+#[test]
+fn recurring_deal_rollover_works() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_recurring(&mut app, true);
+
+    let accept_tranche_msg = ExecuteMsg::AcceptTranche {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+    };
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &accept_tranche_msg,
+        &[Coin::new(500, "usdc")],
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &accept_tranche_msg,
+            &[Coin::new(500, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::DealNotAvailable {},
+        "expected error because the tranche is already filled"
+    );
+
+    // The unfilled tranche of the second interval rolls over to the third one.
+    app.update_block(|block| block.height += 20);
+
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &accept_tranche_msg,
+            &[Coin::new(500, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::WrongCoin {
+            denom: "usdc".to_string(),
+            amount: Uint128::new(1_000)
+        },
+        "expected error because two tranches are available"
+    );
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &accept_tranche_msg,
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.wrap().query_balance(&stepit, "astro").unwrap().amount,
+        Uint128::new(2_940),
+        "expected tranches sent to the counterparty less fee"
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "usdc")
+            .unwrap()
+            .amount,
+        Uint128::new(1_470),
+        "expected tranches paid to the creator less fee"
+    );
+    let resp: AllDealsResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr, &QueryMsg::AllDeals {})
+        .unwrap();
+    assert!(resp.deals.is_empty(), "expected completed deal removed");
+}
+
+#[test]
+fn recurring_deal_refund_and_stop_works() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_recurring(&mut app, false);
+
+    // The unfilled first tranche is refunded when the second one is filled.
+    app.update_block(|block| block.height += 10);
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::AcceptTranche {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+        },
+        &[Coin::new(500, "usdc")],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "astro")
+            .unwrap()
+            .amount,
+        Uint128::new(1_000),
+        "expected unfilled tranche refunded without fee"
+    );
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::Withdraw {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+        },
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "astro")
+            .unwrap()
+            .amount,
+        Uint128::new(2_000),
+        "expected unsold coin refunded when the schedule is stopped"
+    );
+    let resp: AllDealsResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr, &QueryMsg::AllDeals {})
+        .unwrap();
+    assert!(resp.deals.is_empty(), "expected stopped deal removed");
+}
+
+#[test]
+fn recurring_deal_rolls_long_idle_intervals_at_once() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_recurring(&mut app, true);
+
+    // Tranches of a deal left idle for a long time are rolled in a single step.
+    app.update_block(|block| block.height += 1_000_000_000);
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr,
+        &ExecuteMsg::AcceptTranche {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+        },
+        &[Coin::new(1_500, "usdc")],
+    )
+    .unwrap();
+
+    assert_eq!(
+        app.wrap().query_balance(&stepit, "astro").unwrap().amount,
+        Uint128::new(2_940),
+        "expected the whole deposit available after the idle intervals"
+    );
+}
@@ -0,0 +1,257 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
+
+use crate::{
+    error::ContractError,
+    msg::{AllDealsResponse, ExecuteMsg, QueryMsg},
+};
+
+use common::market::InstantiateMsg;
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+#[test]
+fn settle_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+    let keeper = Addr::unchecked("0xkeeper".to_string());
+
+    // Store and instantiate the market contract.
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(
+            market_id,
+            owner.clone(),
+            &init_market_msg,
+            &[],
+            "otc-market",
+            None,
+        )
+        .unwrap();
+
+    // Mint tokens to creator and counterparty
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(2_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(2_000, "usdc")],
+    }))
+    .unwrap();
+
+    // Create and accept two deals
+    let create_deal_msg = ExecuteMsg::CreateDeal {
+        coin_out: Coin::new(1_000, "usdc"),
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
+    };
+    for deal_id in 0..2 {
+        app.execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &create_deal_msg,
+            &[Coin::new(1_000, "astro")],
+        )
+        .unwrap();
+        app.execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::AcceptDeal {
+                creator: not_a_scammer.to_string(),
+                deal_id,
+                expected_coin_in: None,
+                min_receive: None,
+                expected_version: None,
+            },
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap();
+    }
+
+    // Settle the first deal without any withdraw
+    app.execute_contract(
+        keeper.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::Settle {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let creator_balance = app
+        .wrap()
+        .query_balance(not_a_scammer.clone(), "usdc")
+        .unwrap();
+    assert_eq!(
+        creator_balance.amount,
+        Uint128::new(980),
+        "expected creator to receive usdc less the fee"
+    );
+    let counterparty_balance = app.wrap().query_balance(stepit.clone(), "astro").unwrap();
+    assert_eq!(
+        counterparty_balance.amount,
+        Uint128::new(980),
+        "expected counterparty to receive astro less the fee"
+    );
+
+    // Settle the second deal after the creator withdraw
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::Withdraw {
+            creator: not_a_scammer.to_string(),
+            deal_id: 1,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        keeper.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::Settle {
+            creator: not_a_scammer.to_string(),
+            deal_id: 1,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let creator_balance = app
+        .wrap()
+        .query_balance(not_a_scammer.clone(), "usdc")
+        .unwrap();
+    assert_eq!(
+        creator_balance.amount,
+        Uint128::new(1_960),
+        "expected creator to receive usdc only once"
+    );
+    let counterparty_balance = app.wrap().query_balance(stepit.clone(), "astro").unwrap();
+    assert_eq!(
+        counterparty_balance.amount,
+        Uint128::new(1_960),
+        "expected counterparty to receive the remaining astro"
+    );
+
+    let keeper_balance = app.wrap().query_all_balances(keeper).unwrap();
+    assert!(
+        keeper_balance.is_empty(),
+        "expected keeper to receive nothing"
+    );
+
+    let resp: AllDealsResponse = app
+        .wrap()
+        .query_wasm_smart(market_addr.clone(), &QueryMsg::AllDeals {})
+        .unwrap();
+    assert_eq!(resp.deals.len(), 0, "expected settled deals to be removed");
+}
+
+#[test]
+fn settle_handle_errors() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+    let keeper = Addr::unchecked("0xkeeper".to_string());
+
+    // Store and instantiate the market contract.
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(
+            market_id,
+            owner.clone(),
+            &init_market_msg,
+            &[],
+            "otc-market",
+            None,
+        )
+        .unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+
+    let create_deal_msg = ExecuteMsg::CreateDeal {
+        coin_out: Coin::new(1_000, "usdc"),
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
+    };
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &create_deal_msg,
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            keeper.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::Settle {
+                creator: not_a_scammer.to_string(),
+                deal_id: 0,
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::DealNotMatched {},
+        "expected error because deal not matched"
+    );
+}
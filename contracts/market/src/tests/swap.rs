@@ -0,0 +1,255 @@
+use astroport::{
+    asset::AssetInfo,
+    router::{ExecuteMsg as RouterExecuteMsg, SwapOperation},
+};
+use cosmwasm_std::{
+    coin, Addr, BankMsg, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Response,
+    StdError, StdResult,
+};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_storage_plus::Item;
+use cw_utils::Expiration;
+
+use crate::{
+    error::ContractError,
+    msg::{DealsByCreatorResponse, ExecuteMsg, QueryMsg},
+};
+
+use common::market::{DealStatus, InstantiateMsg};
+
+const OWNER: &str = "0xstepit000";
+
+const RATE: Item<Decimal> = Item::new("rate");
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    )
+    .with_reply(crate::contract::reply);
+    Box::new(contract)
+}
+
+// Creates a mock router swapping the sent coin at the rate set during the instantiation.
+pub fn router_contract() -> Box<dyn Contract<Empty>> {
+    fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        rate: Decimal,
+    ) -> StdResult<Response> {
+        RATE.save(deps.storage, &rate)?;
+        Ok(Response::new())
+    }
+    fn execute(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        msg: RouterExecuteMsg,
+    ) -> StdResult<Response> {
+        let RouterExecuteMsg::ExecuteSwapOperations {
+            operations,
+            minimum_receive,
+            ..
+        } = msg
+        else {
+            return Err(StdError::generic_err("unsupported message"));
+        };
+        let Some(SwapOperation::AstroSwap {
+            ask_asset_info: AssetInfo::NativeToken { denom },
+            ..
+        }) = operations.last()
+        else {
+            return Err(StdError::generic_err("unsupported operation"));
+        };
+        let amount = info.funds[0].amount * RATE.load(deps.storage)?;
+        if minimum_receive.is_some_and(|minimum| amount < minimum) {
+            return Err(StdError::generic_err("assertion failed: minimum receive"));
+        }
+        Ok(Response::new().add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![coin(amount.u128(), denom)],
+        }))
+    }
+    fn query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+        Err(StdError::generic_err("no query"))
+    }
+    Box::new(ContractWrapper::new(execute, instantiate, query))
+}
+
+// Instantiates a router swapping one ntrn for two usdc and a market using it, if required. The
+// market has a deal selling 1_000 astro for 500 usdc.
+fn setup_market(app: &mut App, with_router: bool) -> Addr {
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let router_id = app.store_code(router_contract());
+    let router_addr = app
+        .instantiate_contract(
+            router_id,
+            owner.clone(),
+            &Decimal::percent(200),
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: with_router.then(|| router_addr.to_string()),
+    };
+    let market_addr = app
+        .instantiate_contract(market_id, owner, &init_market_msg, &[], "otc-market", None)
+        .unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "ntrn"), Coin::new(500, "usdc")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: router_addr.to_string(),
+        amount: vec![Coin::new(10_000, "usdc")],
+    }))
+    .unwrap();
+
+    app.execute_contract(
+        not_a_scammer,
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(500, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::Never {},
+            match_crossing: None,
+            kind: None,
+            trigger: None,
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    market_addr
+}
+
+// Returns the message to accept the deal of the market paying with another coin.
+fn accept_deal_with_swap_msg() -> ExecuteMsg {
+    ExecuteMsg::AcceptDealWithSwap {
+        creator: "0xtrustme".to_string(),
+        deal_id: 0,
+        max_spread: Some(Decimal::percent(1)),
+        expected_coin_in: None,
+        min_receive: None,
+        expected_version: None,
+    }
+}
+
+#[test]
+fn accept_deal_with_swap_works() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_addr = setup_market(&mut app, true);
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &accept_deal_with_swap_msg(),
+        &[Coin::new(300, "ntrn")],
+    )
+    .unwrap();
+
+    let resp: DealsByCreatorResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr.clone(),
+            &QueryMsg::DealsByCreator {
+                creator: not_a_scammer.to_string(),
+            },
+        )
+        .unwrap();
+    let deal = &resp.deals[0].1;
+    assert_eq!(deal.counterparty, Some(stepit.clone()));
+    assert_eq!(deal.status, DealStatus::matched_no_withdraw());
+
+    let usdc_balance = app.wrap().query_balance(&stepit, "usdc").unwrap();
+    assert_eq!(
+        usdc_balance.amount.u128(),
+        600,
+        "expected the 100 usdc received in excess from the swap to be refunded"
+    );
+    let market_balance = app.wrap().query_balance(&market_addr, "usdc").unwrap();
+    assert_eq!(market_balance.amount.u128(), 500);
+}
+
+#[test]
+fn accept_deal_with_swap_fails() {
+    let mut app: App = App::default();
+
+    let stepit = Addr::unchecked("0xstepit".to_string());
+
+    let market_addr = setup_market(&mut app, false);
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr,
+            &accept_deal_with_swap_msg(),
+            &[Coin::new(300, "ntrn")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::MissingRouter {},
+        "expected error because the market has no router"
+    );
+
+    let market_addr = setup_market(&mut app, true);
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &accept_deal_with_swap_msg(),
+            &[Coin::new(500, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::SwapNotRequired {},
+        "expected error because usdc is the requested coin"
+    );
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &accept_deal_with_swap_msg(),
+        &[Coin::new(200, "ntrn")],
+    )
+    .unwrap_err();
+    let ntrn_balance = app.wrap().query_balance(&stepit, "ntrn").unwrap();
+    assert_eq!(
+        ntrn_balance.amount.u128(),
+        2_000,
+        "expected swap returning less than the requested coin to be reverted"
+    );
+}
@@ -0,0 +1,449 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
+
+use crate::{error::ContractError, msg::ExecuteMsg};
+
+use common::market::{InstantiateMsg, Side};
+
+const OWNER: &str = "0xstepit000";
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+#[test]
+fn transfer_position_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let stepit_cold = Addr::unchecked("0xstepitcold".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+    let not_a_scammer_cold = Addr::unchecked("0xtrustmecold".to_string());
+
+    // Store and instantiate the market contract.
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(0),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(
+            market_id,
+            owner.clone(),
+            &init_market_msg,
+            &[],
+            "otc-market",
+            None,
+        )
+        .unwrap();
+
+    // Mint tokens to creator and counterparty
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "usdc")],
+    }))
+    .unwrap();
+
+    let create_deal_msg = ExecuteMsg::CreateDeal {
+        coin_out: Coin::new(1_000, "usdc"),
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
+    };
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &create_deal_msg,
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    // Creator moves the position to the cold wallet
+    let resp = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::TransferPosition {
+                creator: not_a_scammer.to_string(),
+                deal_id: 0,
+                side: Side::Creator,
+                recipient: not_a_scammer_cold.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+    assert!(
+        resp.has_event(
+            &cosmwasm_std::Event::new("wasm-transfer_position")
+                .add_attribute("side", "creator")
+                .add_attribute("to", not_a_scammer_cold.to_string())
+        ),
+        "expected transfer event"
+    );
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::AcceptDeal {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+            expected_coin_in: None,
+            min_receive: None,
+            expected_version: None,
+        },
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+
+    // Counterparty moves the position to the cold wallet
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::TransferPosition {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+            side: Side::Counterparty,
+            recipient: stepit_cold.to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let withdraw_msg = ExecuteMsg::Withdraw {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+    };
+
+    // Previous holders cannot withdraw anymore
+    for sender in [not_a_scammer.clone(), stepit.clone()] {
+        let err = app
+            .execute_contract(sender, market_addr.clone(), &withdraw_msg, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ContractError>().unwrap(),
+            &ContractError::Unauthorized {},
+            "expected error because position transferred"
+        );
+    }
+
+    app.execute_contract(
+        not_a_scammer_cold.clone(),
+        market_addr.clone(),
+        &withdraw_msg,
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(stepit_cold.clone(), market_addr.clone(), &withdraw_msg, &[])
+        .unwrap();
+
+    let creator_balance = app
+        .wrap()
+        .query_balance(not_a_scammer_cold, "usdc")
+        .unwrap();
+    assert_eq!(
+        creator_balance.amount,
+        Uint128::new(1_000),
+        "expected new creator holder to receive usdc"
+    );
+    let counterparty_balance = app.wrap().query_balance(stepit_cold, "astro").unwrap();
+    assert_eq!(
+        counterparty_balance.amount,
+        Uint128::new(1_000),
+        "expected new counterparty holder to receive astro"
+    );
+}
+
+#[test]
+fn transfer_position_handle_errors() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    // Store and instantiate the market contract.
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(0),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(
+            market_id,
+            owner.clone(),
+            &init_market_msg,
+            &[],
+            "otc-market",
+            None,
+        )
+        .unwrap();
+
+    // Mint tokens to creator and counterparty
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(1_000, "usdc")],
+    }))
+    .unwrap();
+
+    let create_deal_msg = ExecuteMsg::CreateDeal {
+        coin_out: Coin::new(1_000, "usdc"),
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
+    };
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &create_deal_msg,
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::TransferPosition {
+                creator: not_a_scammer.to_string(),
+                deal_id: 0,
+                side: Side::Creator,
+                recipient: stepit.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::Unauthorized {},
+        "expected error because sender does not hold the position"
+    );
+
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::TransferPosition {
+                creator: not_a_scammer.to_string(),
+                deal_id: 0,
+                side: Side::Counterparty,
+                recipient: stepit.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::Unauthorized {},
+        "expected error because deal not matched"
+    );
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::AcceptDeal {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+            expected_coin_in: None,
+            min_receive: None,
+            expected_version: None,
+        },
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::Withdraw {
+            creator: not_a_scammer.to_string(),
+            deal_id: 0,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::TransferPosition {
+                creator: not_a_scammer.to_string(),
+                deal_id: 0,
+                side: Side::Creator,
+                recipient: stepit.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::PositionWithdrawn {},
+        "expected error because creator already withdrawn"
+    );
+}
+
+#[test]
+fn withdraw_both_positions_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(0),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(market_id, owner, &init_market_msg, &[], "otc-market", None)
+        .unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(2_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(2_000, "usdc")],
+    }))
+    .unwrap();
+
+    for deal_id in 0..2 {
+        app.execute_contract(
+            not_a_scammer.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::CreateDeal {
+                coin_out: Coin::new(1_000, "usdc"),
+                counterparties: vec![],
+                group: None,
+                expiry: Expiration::Never {},
+                match_crossing: None,
+                kind: None,
+                trigger: None,
+                coin_out_alternatives: vec![],
+            },
+            &[Coin::new(1_000, "astro")],
+        )
+        .unwrap();
+        app.execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &ExecuteMsg::AcceptDeal {
+                creator: not_a_scammer.to_string(),
+                deal_id,
+                expected_coin_in: None,
+                min_receive: None,
+                expected_version: None,
+            },
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap();
+    }
+
+    let withdraw_msg = |deal_id| ExecuteMsg::Withdraw {
+        creator: not_a_scammer.to_string(),
+        deal_id,
+    };
+    let transfer_msg = |deal_id| ExecuteMsg::TransferPosition {
+        creator: not_a_scammer.to_string(),
+        deal_id,
+        side: Side::Counterparty,
+        recipient: not_a_scammer.to_string(),
+    };
+
+    // The creator already withdrew and receives the counterparty position.
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &withdraw_msg(0),
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(stepit.clone(), market_addr.clone(), &transfer_msg(0), &[])
+        .unwrap();
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &withdraw_msg(0),
+        &[],
+    )
+    .unwrap();
+
+    // The creator holds both the positions before withdrawing.
+    app.execute_contract(stepit, market_addr.clone(), &transfer_msg(1), &[])
+        .unwrap();
+    app.execute_contract(not_a_scammer.clone(), market_addr, &withdraw_msg(1), &[])
+        .unwrap();
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "astro")
+            .unwrap()
+            .amount,
+        Uint128::new(2_000),
+        "expected both the counterparty legs withdrawn"
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(&not_a_scammer, "usdc")
+            .unwrap()
+            .amount,
+        Uint128::new(2_000),
+        "expected both the creator legs withdrawn"
+    );
+}
@@ -0,0 +1,309 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Response,
+    StdResult,
+};
+use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_storage_plus::Map;
+use cw_utils::Expiration;
+
+use crate::{
+    error::ContractError,
+    msg::{ExecuteMsg, OrderBookResponse, QueryMsg},
+};
+
+use common::{
+    market::{InstantiateMsg, PriceTrigger},
+    oracle::{OracleQueryMsg, PriceResponse},
+};
+
+const OWNER: &str = "0xstepit000";
+
+const PRICES: Map<(&str, &str), Decimal> = Map::new("prices");
+
+// Price of the base denom in units of the quote denom set in the mock oracle.
+#[cw_serde]
+pub struct SetPrice {
+    pub base: String,
+    pub quote: String,
+    pub price: Decimal,
+}
+
+impl SetPrice {
+    fn new(base: &str, quote: &str, price: Decimal) -> Self {
+        SetPrice {
+            base: base.to_string(),
+            quote: quote.to_string(),
+            price,
+        }
+    }
+}
+
+// Creates a market contract.
+pub fn market_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    );
+    Box::new(contract)
+}
+
+// Creates a mock oracle contract returning the prices set by instantiate or execute.
+pub fn oracle_contract() -> Box<dyn Contract<Empty>> {
+    fn set_price(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: SetPrice,
+    ) -> StdResult<Response> {
+        PRICES.save(deps.storage, (&msg.base, &msg.quote), &msg.price)?;
+        Ok(Response::new())
+    }
+    fn query(deps: Deps, _env: Env, msg: OracleQueryMsg) -> StdResult<Binary> {
+        match msg {
+            OracleQueryMsg::Price { base, quote } => to_json_binary(&PriceResponse {
+                price: PRICES.load(deps.storage, (&base, &quote))?,
+            }),
+        }
+    }
+    Box::new(ContractWrapper::new(set_price, set_price, query))
+}
+
+// Instantiates an oracle with an astro price of one usdc and a market using it, if required.
+fn setup_market(app: &mut App, with_oracle: bool) -> (Addr, Addr) {
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let oracle_id = app.store_code(oracle_contract());
+    let oracle_addr = app
+        .instantiate_contract(
+            oracle_id,
+            owner.clone(),
+            &SetPrice::new("astro", "usdc", Decimal::one()),
+            &[],
+            "oracle",
+            None,
+        )
+        .unwrap();
+
+    let market_id = app.store_code(market_contract());
+    let init_market_msg = InstantiateMsg {
+        first_coin: "astro".to_string(),
+        second_coin: "usdc".to_string(),
+        fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: with_oracle.then(|| oracle_addr.to_string()),
+        other_coins: Some(vec!["usdt".to_string()]),
+        router: None,
+    };
+    let market_addr = app
+        .instantiate_contract(market_id, owner, &init_market_msg, &[], "otc-market", None)
+        .unwrap();
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "astro")],
+    }))
+    .unwrap();
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: stepit.to_string(),
+        amount: vec![Coin::new(2_000, "usdc")],
+    }))
+    .unwrap();
+
+    (market_addr, oracle_addr)
+}
+
+// Returns the message to create a deal selling 1_000 astro for 2_000 usdc when the price of astro
+// reaches 2 usdc.
+fn create_deal_msg(app: &App) -> ExecuteMsg {
+    ExecuteMsg::CreateDeal {
+        coin_out: Coin::new(2_000, "usdc"),
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: None,
+        kind: None,
+        trigger: Some(PriceTrigger::Above(Decimal::percent(200))),
+        coin_out_alternatives: vec![],
+    }
+}
+
+#[test]
+fn price_trigger_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let (market_addr, oracle_addr) = setup_market(&mut app, true);
+
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &create_deal_msg(&app),
+        &[Coin::new(1_000, "astro")],
+    )
+    .unwrap();
+
+    let resp: OrderBookResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_addr.clone(),
+            &QueryMsg::OrderBook {
+                depth: None,
+                first_denom: None,
+                second_denom: None,
+            },
+        )
+        .unwrap();
+    assert!(
+        resp.sell_first_coin.is_empty(),
+        "expected conditional deal not to be part of the book"
+    );
+
+    let accept_deal_msg = ExecuteMsg::AcceptDeal {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+        expected_coin_in: None,
+        min_receive: None,
+        expected_version: None,
+    };
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &accept_deal_msg,
+            &[Coin::new(2_000, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::TriggerNotReached {},
+        "expected error because the price is lower than the trigger"
+    );
+
+    app.execute_contract(
+        owner,
+        oracle_addr,
+        &SetPrice::new("astro", "usdc", Decimal::percent(210)),
+        &[],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        stepit.clone(),
+        market_addr,
+        &accept_deal_msg,
+        &[Coin::new(2_000, "usdc")],
+    )
+    .unwrap();
+}
+
+#[test]
+fn price_trigger_without_oracle_fails() {
+    let mut app: App = App::default();
+
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let (market_addr, _) = setup_market(&mut app, false);
+
+    let err = app
+        .execute_contract(
+            not_a_scammer,
+            market_addr,
+            &create_deal_msg(&app),
+            &[Coin::new(1_000, "astro")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::MissingOracle {},
+        "expected error because the market has no oracle"
+    );
+}
+
+#[test]
+fn price_trigger_on_other_coin_works() {
+    let mut app: App = App::default();
+
+    let owner = Addr::unchecked(OWNER);
+    let stepit = Addr::unchecked("0xstepit".to_string());
+    let not_a_scammer = Addr::unchecked("0xtrustme".to_string());
+
+    let (market_addr, oracle_addr) = setup_market(&mut app, true);
+
+    app.sudo(SudoMsg::Bank(BankSudo::Mint {
+        to_address: not_a_scammer.to_string(),
+        amount: vec![Coin::new(1_000, "usdt")],
+    }))
+    .unwrap();
+    app.execute_contract(
+        owner.clone(),
+        oracle_addr.clone(),
+        &SetPrice::new("usdc", "usdt", Decimal::percent(101)),
+        &[],
+    )
+    .unwrap();
+
+    // Sell usdt for usdc when the price of usdc falls to 1 usdt.
+    app.execute_contract(
+        not_a_scammer.clone(),
+        market_addr.clone(),
+        &ExecuteMsg::CreateDeal {
+            coin_out: Coin::new(1_000, "usdc"),
+            counterparties: vec![],
+            group: None,
+            expiry: Expiration::AtHeight(app.block_info().height + 10),
+            match_crossing: None,
+            kind: None,
+            trigger: Some(PriceTrigger::Below(Decimal::one())),
+            coin_out_alternatives: vec![],
+        },
+        &[Coin::new(1_000, "usdt")],
+    )
+    .unwrap();
+
+    let accept_deal_msg = ExecuteMsg::AcceptDeal {
+        creator: not_a_scammer.to_string(),
+        deal_id: 0,
+        expected_coin_in: None,
+        min_receive: None,
+        expected_version: None,
+    };
+    let err = app
+        .execute_contract(
+            stepit.clone(),
+            market_addr.clone(),
+            &accept_deal_msg,
+            &[Coin::new(1_000, "usdc")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::TriggerNotReached {},
+        "expected error because the usdc price is higher than the trigger"
+    );
+
+    app.execute_contract(
+        owner,
+        oracle_addr,
+        &SetPrice::new("usdc", "usdt", Decimal::percent(99)),
+        &[],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        stepit,
+        market_addr,
+        &accept_deal_msg,
+        &[Coin::new(1_000, "usdc")],
+    )
+    .unwrap();
+}
@@ -2,6 +2,7 @@ use std::ops::Sub;
 
 use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
 use cw_multi_test::{App, BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
+use cw_utils::Expiration;
 
 use crate::{
     error::ContractError,
@@ -36,6 +37,13 @@ fn withdraw_works_no_fee() {
         first_coin: "astro".to_string(),
         second_coin: "usdc".to_string(),
         fee: Decimal::percent(0),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
     };
     let market_addr = app
         .instantiate_contract(
@@ -75,8 +83,13 @@ fn withdraw_works_no_fee() {
     // Create first deal
     let create_deal_msg = ExecuteMsg::CreateDeal {
         coin_out: Coin::new(1_000, "usdc"),
-        counterparty: None,
-        timeout: 10,
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
     };
     app.execute_contract(
         not_a_scammer.clone(),
@@ -90,6 +103,9 @@ fn withdraw_works_no_fee() {
     let accept_deal_msg = ExecuteMsg::AcceptDeal {
         creator: not_a_scammer.to_string(),
         deal_id: 0,
+        expected_coin_in: None,
+        min_receive: None,
+        expected_version: None,
     };
     app.execute_contract(
         stepit.clone(),
@@ -214,6 +230,13 @@ fn withdraw_works_with_fee() {
         first_coin: "astro".to_string(),
         second_coin: "usdc".to_string(),
         fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
     };
     let market_addr = app
         .instantiate_contract(
@@ -253,8 +276,13 @@ fn withdraw_works_with_fee() {
     // Create first deal
     let create_deal_msg = ExecuteMsg::CreateDeal {
         coin_out: Coin::new(1_000, "usdc"),
-        counterparty: None,
-        timeout: 10,
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
     };
     app.execute_contract(
         not_a_scammer.clone(),
@@ -268,6 +296,9 @@ fn withdraw_works_with_fee() {
     let accept_deal_msg = ExecuteMsg::AcceptDeal {
         creator: not_a_scammer.to_string(),
         deal_id: 0,
+        expected_coin_in: None,
+        min_receive: None,
+        expected_version: None,
     };
     app.execute_contract(
         stepit.clone(),
@@ -377,6 +408,13 @@ fn withdraw_before_matching() {
         first_coin: "astro".to_string(),
         second_coin: "usdc".to_string(),
         fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
     };
     let market_addr = app
         .instantiate_contract(
@@ -408,8 +446,13 @@ fn withdraw_before_matching() {
     // Create first deal
     let create_deal_msg = ExecuteMsg::CreateDeal {
         coin_out: Coin::new(1_000, "usdc"),
-        counterparty: None,
-        timeout: 10,
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
     };
     app.execute_contract(
         not_a_scammer.clone(),
@@ -476,6 +519,13 @@ fn withdraw_handle_errors() {
         first_coin: "astro".to_string(),
         second_coin: "usdc".to_string(),
         fee: Decimal::percent(2),
+        positions_code_id: None,
+        limits: None,
+        group: None,
+        factory: None,
+        oracle: None,
+        other_coins: None,
+        router: None,
     };
     let market_addr = app
         .instantiate_contract(
@@ -509,8 +559,13 @@ fn withdraw_handle_errors() {
     // Create first deal
     let create_deal_msg = ExecuteMsg::CreateDeal {
         coin_out: Coin::new(1_000, "usdc"),
-        counterparty: None,
-        timeout: 10,
+        counterparties: vec![],
+        group: None,
+        expiry: Expiration::AtHeight(app.block_info().height + 10),
+        match_crossing: None,
+        kind: None,
+        trigger: None,
+        coin_out_alternatives: vec![],
     };
     app.execute_contract(
         not_a_scammer.clone(),
@@ -524,6 +579,9 @@ fn withdraw_handle_errors() {
     let accept_deal_msg = ExecuteMsg::AcceptDeal {
         creator: not_a_scammer.to_string(),
         deal_id: 0,
+        expected_coin_in: None,
+        min_receive: None,
+        expected_version: None,
     };
     app.execute_contract(
         stepit.clone(),
@@ -0,0 +1,210 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult};
+use cw721_base::Cw721Contract;
+
+use common::{
+    factory::{DenylistQueryMsg, IsDeniedResponse},
+    market::{Config as MarketConfig, ConfigQueryMsg},
+};
+
+use crate::{
+    error::ContractError,
+    msg::{ExecuteMsg, Extension, InstantiateMsg, PositionsExecuteMsg, QueryMsg},
+};
+
+const CONTRACT_NAME: &str = "crates.io/cw-otc-positions";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// CW721 implementation used to store the positions.
+pub type PositionsContract<'a> = Cw721Contract<'a, Extension, Empty, PositionsExecuteMsg, Empty>;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(PositionsContract::default().instantiate(deps, env, info, msg)?)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        // Positions are burned only by the minter, once the associated coins have been withdrawn.
+        ExecuteMsg::Extension {
+            msg: PositionsExecuteMsg::Burn { token_id },
+        }
+        | ExecuteMsg::Burn { token_id } => execute::burn(deps, info, token_id),
+        // Positions cannot be moved from or to an address denied by the market.
+        ExecuteMsg::TransferNft { ref recipient, .. }
+        | ExecuteMsg::SendNft {
+            contract: ref recipient,
+            ..
+        } => {
+            let recipient = deps.api.addr_validate(recipient)?;
+            execute::check_not_denied(deps.as_ref(), &info.sender)?;
+            execute::check_not_denied(deps.as_ref(), &recipient)?;
+            Ok(PositionsContract::default().execute(deps, env, info, msg)?)
+        }
+        _ => Ok(PositionsContract::default().execute(deps, env, info, msg)?),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    PositionsContract::default().query(deps, env, msg)
+}
+
+pub mod execute {
+    use super::*;
+
+    /// Allows the minter to burn a position once the associated coins have been withdrawn.
+    pub fn burn(
+        deps: DepsMut,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        let contract = PositionsContract::default();
+        contract.tokens.remove(deps.storage, &token_id)?;
+        contract.decrement_tokens(deps.storage)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "burn")
+            .add_attribute("token_id", token_id))
+    }
+
+    /// Check that the address is not in the denylist of the factory consulted by the minter
+    /// market, if any.
+    pub fn check_not_denied(deps: Deps, addr: &Addr) -> Result<(), ContractError> {
+        let Some(market) = cw_ownable::get_ownership(deps.storage)?.owner else {
+            return Ok(());
+        };
+        let config: MarketConfig = deps
+            .querier
+            .query_wasm_smart(market, &ConfigQueryMsg::Config {})?;
+        let Some(factory) = config.factory else {
+            return Ok(());
+        };
+        let resp: IsDeniedResponse = deps.querier.query_wasm_smart(
+            factory,
+            &DenylistQueryMsg::IsDenied {
+                address: addr.to_string(),
+            },
+        )?;
+        if resp.denied {
+            return Err(ContractError::Denied {
+                addr: addr.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Unit tests
+// -------------------------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        from_json,
+        testing::{mock_dependencies, mock_env, mock_info},
+        StdError,
+    };
+    use cw721::OwnerOfResponse;
+
+    use super::*;
+
+    const MARKET: &str = "market";
+
+    #[test]
+    fn burn_works() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(MARKET, &[]),
+            InstantiateMsg {
+                name: "positions".to_string(),
+                symbol: "POS".to_string(),
+                minter: MARKET.to_string(),
+            },
+        )
+        .unwrap();
+
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(MARKET, &[]),
+            ExecuteMsg::Mint {
+                token_id: "0-creator".to_string(),
+                owner: "stepit".to_string(),
+                token_uri: None,
+                extension: None,
+            },
+        )
+        .unwrap();
+
+        let resp: OwnerOfResponse = from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::OwnerOf {
+                    token_id: "0-creator".to_string(),
+                    include_expired: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(resp.owner, "stepit", "expected position minted to owner");
+
+        let burn_msg = ExecuteMsg::Extension {
+            msg: PositionsExecuteMsg::Burn {
+                token_id: "0-creator".to_string(),
+            },
+        };
+        for msg in [
+            burn_msg.clone(),
+            ExecuteMsg::Burn {
+                token_id: "0-creator".to_string(),
+            },
+        ] {
+            let err =
+                execute(deps.as_mut(), env.clone(), mock_info("stepit", &[]), msg).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::Ownership(cw_ownable::OwnershipError::NotOwner),
+                "expected error because only the minter can burn"
+            );
+        }
+
+        execute(deps.as_mut(), env.clone(), mock_info(MARKET, &[]), burn_msg).unwrap();
+
+        let err = query(
+            deps.as_ref(),
+            env,
+            QueryMsg::OwnerOf {
+                token_id: "0-creator".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, StdError::NotFound { .. }),
+            "expected position to be burned"
+        );
+    }
+}
@@ -0,0 +1,3 @@
+pub mod contract;
+mod error;
+pub mod msg;
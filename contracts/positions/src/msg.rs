@@ -0,0 +1,23 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{CustomMsg, Empty};
+
+/// Positions do not store any additional metadata.
+pub type Extension = Option<Empty>;
+
+/// This struct contains required variables to instantiate a new positions contract.
+pub type InstantiateMsg = cw721_base::InstantiateMsg;
+
+/// This enum describes available contract's execution messages.
+pub type ExecuteMsg = cw721_base::ExecuteMsg<Extension, PositionsExecuteMsg>;
+
+/// This enum describes available contract's query messages.
+pub type QueryMsg = cw721_base::QueryMsg<Empty>;
+
+/// Execution messages added to the standard CW721 ones.
+#[cw_serde]
+pub enum PositionsExecuteMsg {
+    /// Allows the minter to burn a position independently from the owner. Only minter can burn.
+    Burn { token_id: String },
+}
+
+impl CustomMsg for PositionsExecuteMsg {}
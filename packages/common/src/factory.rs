@@ -4,10 +4,27 @@ use cosmwasm_std::Addr;
 /// This struct contains configuration parameters for the contract.
 #[cw_serde]
 pub struct Config {
-    /// Contract owner
-    pub owner: Addr,
     /// Code ID of the market contract.
     pub market_code_id: u64,
     /// Optional address used to collect markets fees.
     pub fee_collector: Option<Addr>,
+    /// Optional code ID of the positions contract used by markets to tokenize deals.
+    pub positions_code_id: Option<u64>,
+    /// If true, denied addresses can still withdraw the coins of their unmatched deals.
+    pub denied_can_withdraw: bool,
+}
+
+/// Queries of the denylist maintained by the factory, consulted by the markets.
+#[cw_serde]
+pub enum DenylistQueryMsg {
+    /// Retrieve if an address is denied.
+    IsDenied { address: String },
+}
+
+#[cw_serde]
+pub struct IsDeniedResponse {
+    /// True if the address is in the denylist.
+    pub denied: bool,
+    /// True if denied addresses are allowed to withdraw the coins of unmatched deals.
+    pub can_withdraw: bool,
 }
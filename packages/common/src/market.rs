@@ -1,5 +1,8 @@
+use std::fmt;
+
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Coin, Decimal};
+use cosmwasm_std::{Addr, Coin, Decimal, Uint128};
+use cw_utils::{Duration, Expiration, Scheduled};
 
 /// This struct contains required variables to instantiate a new market.
 #[cw_serde]
@@ -10,19 +13,81 @@ pub struct InstantiateMsg {
     pub second_coin: String,
     /// Fee deducted from each exchange in bps.
     pub fee: Decimal,
+    /// Code ID of the positions contract. If specified, deal positions are represented as CW721
+    /// tokens and withdraws are authorized to the tokens owners.
+    pub positions_code_id: Option<u64>,
+    /// Limits applied to the deals of the market. If not specified, no limit is applied.
+    pub limits: Option<DealLimits>,
+    /// Address of a CW4 group contract. If specified, only its members can create or accept deals.
+    pub group: Option<String>,
+    /// Address of the factory whose denylist is consulted by the market, if any.
+    pub factory: Option<String>,
+    /// Address of the price oracle consulted by deals with a price trigger, if any.
+    pub oracle: Option<String>,
+    /// Other coins accepted by the deals of the market besides the first and second coin.
+    pub other_coins: Option<Vec<String>>,
+    /// Address of the Astroport router used to swap the coin sent by takers paying with a
+    /// different token, if any.
+    pub router: Option<String>,
+}
+
+/// Query of the market configuration, consulted by the positions contract.
+#[cw_serde]
+pub enum ConfigQueryMsg {
+    /// Retrieve the market configuration.
+    Config {},
 }
 
 /// This struct contains configuration parameters for the market.
 #[cw_serde]
 pub struct Config {
-    /// Address of the instantiatooor of the contract. It should be the factory contract.
-    pub owner: Addr,
     /// First coin exchanged in this market.
     pub first_coin: String,
     /// Second coin exchanged in this market.
     pub second_coin: String,
     /// Fee deducted from each exchange in percentage.
     pub fee: Decimal,
+    /// Address of the CW721 contract representing the deal positions, if any.
+    pub positions: Option<Addr>,
+    /// Limits applied to the deals of the market.
+    pub limits: DealLimits,
+    /// Address of the CW4 group whose members are the only ones allowed to create or accept deals.
+    pub group: Option<Addr>,
+    /// Address of the factory whose denylist is consulted by the market, if any.
+    pub factory: Option<Addr>,
+    /// Address of the price oracle consulted by deals with a price trigger, if any.
+    pub oracle: Option<Addr>,
+    /// Other coins accepted by the deals of the market besides the first and second coin. The
+    /// order book can be queried and taken for any pair of the market coins.
+    pub other_coins: Vec<String>,
+    /// Address of the Astroport router used to swap the coin sent by takers paying with a
+    /// different token, if any.
+    pub router: Option<Addr>,
+}
+
+/// Limits applied to the deals created in a market. The expiry of a deal must be expressed in the
+/// same unit of the duration limits, if any.
+#[cw_serde]
+#[derive(Default)]
+pub struct DealLimits {
+    /// Minimum number of blocks, or seconds, before a deal expires.
+    pub min_duration: Option<Duration>,
+    /// Maximum number of blocks, or seconds, before a deal expires. If specified, deals that never
+    /// expire are not allowed.
+    pub max_duration: Option<Duration>,
+    /// Amount limits of the market coins, applied both to the offered and the requested coin.
+    pub amounts: Vec<AmountLimits>,
+}
+
+/// Minimum and maximum amounts of a coin in a deal.
+#[cw_serde]
+pub struct AmountLimits {
+    /// Denom of the coin.
+    pub denom: String,
+    /// Minimum amount of the coin.
+    pub min: Option<Uint128>,
+    /// Maximum amount of the coin.
+    pub max: Option<Uint128>,
 }
 
 /// Contains all information of a Deal.
@@ -32,12 +97,78 @@ pub struct Deal {
     pub coin_in: Coin,
     /// Coin that the user wants to receive.
     pub coin_out: Coin,
-    /// Only address that can accept the deal.
+    /// Coins that the user accepts instead of the requested coin, until the deal is matched.
+    pub coin_out_alternatives: Vec<Coin>,
+    /// Addresses allowed to accept the deal. If empty, anyone can accept it.
+    pub counterparties: Vec<Addr>,
+    /// CW4 group whose members are the only ones allowed to accept the deal, as an alternative to
+    /// the counterparties.
+    pub group: Option<Addr>,
+    /// Address that accepted the deal.
     pub counterparty: Option<Addr>,
-    /// Block after which the deal expire.
-    pub timeout: u64,
+    /// Height, time, or never after which the deal expires.
+    pub expiry: Expiration,
     /// Status of the deal.
     pub status: DealStatus,
+    /// Address holding the creator position if transferred by the creator.
+    pub creator_holder: Option<Addr>,
+    /// Number of times the deal has been amended by the creator.
+    pub version: u64,
+    /// Kind of the deal.
+    pub kind: DealKind,
+    /// If specified, the deal can be accepted only when the oracle price satisfies the trigger.
+    pub trigger: Option<PriceTrigger>,
+    /// One-cancels-other group of the deal, if any. When a deal of the group is accepted, the
+    /// others are cancelled and refunded.
+    pub oco: Option<u64>,
+}
+
+/// Condition on the oracle price of the first coin of the deal pair, in units of the other one,
+/// required to accept a deal. The coins of the pair are ordered as in the market: first coin,
+/// second coin, and then the other coins.
+#[cw_serde]
+pub enum PriceTrigger {
+    /// The deal can be accepted when the price is higher than or equal to the threshold.
+    Above(Decimal),
+    /// The deal can be accepted when the price is lower than or equal to the threshold.
+    Below(Decimal),
+}
+
+/// Describes the possible kinds of a deal.
+#[cw_serde]
+#[derive(Default)]
+pub enum DealKind {
+    /// Coins are exchanged as soon as the deal is accepted.
+    #[default]
+    Spot,
+    /// Covered option on the offered coin. The counterparty buys the option paying the premium,
+    /// which is sent to the creator immediately, and gets the right to exercise it paying the
+    /// requested coin before expiry. A call deposits the underlying and requests the strike, a put
+    /// deposits the strike and requests the underlying.
+    Option {
+        /// Coin paid by the counterparty to buy the option.
+        premium: Coin,
+    },
+    /// Forward contract. Both the parties lock a fraction of their coin when the deal is agreed and
+    /// have to fund the rest before the settlement height or time, after which the coins are
+    /// exchanged. A party that does not fully fund its coin defaults and loses its deposit in
+    /// favor of the other one.
+    Forward {
+        /// Height or time after which the deal can be settled.
+        settle_at: Scheduled,
+        /// Fraction of the coins locked when the deal is agreed.
+        collateral: Decimal,
+    },
+    /// Recurring deal. The deposited coin is sold in tranches, one every interval, each one for
+    /// the requested coin. Unfilled tranches are added to the next one or refunded to the creator.
+    Recurring {
+        /// Blocks or time after which a new tranche is available.
+        interval: Duration,
+        /// Amount of the offered coin available in each tranche.
+        tranche: Uint128,
+        /// If true, unfilled tranches are added to the next one, otherwise they are refunded.
+        rollover: bool,
+    },
 }
 
 /// Describes the possible status of a deal.
@@ -45,6 +176,40 @@ pub struct Deal {
 pub enum DealStatus {
     NotMatched,
     Matched(WithdrawStatus),
+    /// Option bought by the counterparty and not yet exercised.
+    OptionBought,
+    /// Forward agreed by the counterparty and not yet settled.
+    ForwardAgreed {
+        /// Amount of the offered coin deposited by the creator.
+        creator_funded: Uint128,
+        /// Amount of the requested coin deposited by the counterparty.
+        counterparty_funded: Uint128,
+    },
+    /// Recurring deal with a tranche available until the end of the current interval.
+    Recurring {
+        /// Height or time at which the current tranche ends.
+        tranche_end: Expiration,
+        /// Amount of the offered coin available in the current tranche.
+        available: Uint128,
+    },
+}
+
+/// Describes the two positions of a deal.
+#[cw_serde]
+pub enum Side {
+    /// Position of the deal creator.
+    Creator,
+    /// Position of the address that accepted the deal.
+    Counterparty,
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Side::Creator => write!(f, "creator"),
+            Side::Counterparty => write!(f, "counterparty"),
+        }
+    }
 }
 
 /// Describes the possible status of a matched deal.
@@ -77,3 +242,26 @@ impl DealStatus {
         DealStatus::Matched(WithdrawStatus::Completed)
     }
 }
+
+/// Contains the terms of a deal signed off-chain by a maker. The signed message is the SHA-256
+/// hash of the JSON serialization of this struct.
+#[cw_serde]
+pub struct Offer {
+    /// Identifier of the chain in which the offer can be accepted, to prevent its replay on other
+    /// chains.
+    pub chain_id: String,
+    /// Address of the market in which the offer can be accepted.
+    pub market: String,
+    /// Address of the maker that signed the offer.
+    pub maker: String,
+    /// Coin that the maker wants to swap. It is debited from the maker's balance.
+    pub coin_in: Coin,
+    /// Coin that the maker wants to receive.
+    pub coin_out: Coin,
+    /// Only address that can accept the offer.
+    pub counterparty: Option<String>,
+    /// Unique number used to prevent the offer from being accepted twice.
+    pub nonce: u64,
+    /// Height, time, or never after which the offer expires.
+    pub expiry: Expiration,
+}
@@ -0,0 +1,15 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Decimal;
+
+/// Queries of the price oracle consulted by the markets.
+#[cw_serde]
+pub enum OracleQueryMsg {
+    /// Retrieve the price of the base denom in units of the quote denom.
+    Price { base: String, quote: String },
+}
+
+#[cw_serde]
+pub struct PriceResponse {
+    /// Price of one unit of the base denom in units of the quote denom.
+    pub price: Decimal,
+}